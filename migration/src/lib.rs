@@ -1,12 +1,40 @@
 pub use sea_orm_migration::prelude::*;
 
 mod migration_01_init_table;
+mod migration_02_create_trust_table;
+mod migration_03_create_report_table;
+mod migration_04_create_shadow_ban_table;
+mod migration_05_add_comment_deleted_at;
+mod migration_06_create_comment_revision_table;
+mod migration_07_create_like_table;
+mod migration_08_create_reaction_table;
+mod migration_09_create_path_setting_table;
+mod migration_10_add_comment_locked;
+mod migration_11_add_comment_revision_mail;
+mod migration_12_add_like_unique_indexes;
+mod migration_13_add_reaction_unique_indexes;
+mod migration_14_normalize_paths;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
   fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-    vec![Box::new(migration_01_init_table::Migration)]
+    vec![
+      Box::new(migration_01_init_table::Migration),
+      Box::new(migration_02_create_trust_table::Migration),
+      Box::new(migration_03_create_report_table::Migration),
+      Box::new(migration_04_create_shadow_ban_table::Migration),
+      Box::new(migration_05_add_comment_deleted_at::Migration),
+      Box::new(migration_06_create_comment_revision_table::Migration),
+      Box::new(migration_07_create_like_table::Migration),
+      Box::new(migration_08_create_reaction_table::Migration),
+      Box::new(migration_09_create_path_setting_table::Migration),
+      Box::new(migration_10_add_comment_locked::Migration),
+      Box::new(migration_11_add_comment_revision_mail::Migration),
+      Box::new(migration_12_add_like_unique_indexes::Migration),
+      Box::new(migration_13_add_reaction_unique_indexes::Migration),
+      Box::new(migration_14_normalize_paths::Migration),
+    ]
   }
 }
@@ -0,0 +1,44 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WlTrust::Table)
+          .if_not_exists()
+          .col(pk_auto(WlTrust::Id).unsigned())
+          .col(integer_null(WlTrust::UserId))
+          .col(string_null(WlTrust::Mail))
+          .col(boolean(WlTrust::Trusted))
+          .col(timestamp_null(WlTrust::CreatedAt).default(Expr::current_timestamp()))
+          .col(timestamp_null(WlTrust::UpdatedAt).default(Expr::current_timestamp()))
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(WlTrust::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlTrust {
+  #[sea_orm(iden = "wl_Trust")]
+  Table,
+  Id,
+  UserId,
+  Mail,
+  Trusted,
+  #[sea_orm(iden = "createdAt")]
+  CreatedAt,
+  #[sea_orm(iden = "updatedAt")]
+  UpdatedAt,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WlReport::Table)
+          .if_not_exists()
+          .col(pk_auto(WlReport::Id).unsigned())
+          .col(integer(WlReport::CommentId))
+          .col(integer_null(WlReport::UserId))
+          .col(string_null(WlReport::Ip))
+          .col(string_null(WlReport::Reason))
+          .col(timestamp_null(WlReport::CreatedAt).default(Expr::current_timestamp()))
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_report_comment_id")
+          .table(WlReport::Table)
+          .col(WlReport::CommentId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(WlReport::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlReport {
+  #[sea_orm(iden = "wl_Report")]
+  Table,
+  Id,
+  CommentId,
+  UserId,
+  Ip,
+  Reason,
+  #[sea_orm(iden = "createdAt")]
+  CreatedAt,
+}
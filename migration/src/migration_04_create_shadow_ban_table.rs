@@ -0,0 +1,41 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WlShadowBan::Table)
+          .if_not_exists()
+          .col(pk_auto(WlShadowBan::Id).unsigned())
+          .col(integer_null(WlShadowBan::UserId))
+          .col(string_null(WlShadowBan::Mail))
+          .col(string_null(WlShadowBan::Ip))
+          .col(timestamp_null(WlShadowBan::CreatedAt).default(Expr::current_timestamp()))
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(WlShadowBan::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlShadowBan {
+  #[sea_orm(iden = "wl_ShadowBan")]
+  Table,
+  Id,
+  UserId,
+  Mail,
+  Ip,
+  #[sea_orm(iden = "createdAt")]
+  CreatedAt,
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(WlComment::Table)
+          .add_column(timestamp_null(WlComment::DeletedAt))
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(WlComment::Table)
+          .drop_column(WlComment::DeletedAt)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlComment {
+  #[sea_orm(iden = "wl_Comment")]
+  Table,
+  #[sea_orm(iden = "deletedAt")]
+  DeletedAt,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WlCommentRevision::Table)
+          .if_not_exists()
+          .col(pk_auto(WlCommentRevision::Id).unsigned())
+          .col(integer(WlCommentRevision::CommentId))
+          .col(text_null(WlCommentRevision::Comment))
+          .col(integer_null(WlCommentRevision::UserId))
+          .col(timestamp_null(WlCommentRevision::CreatedAt).default(Expr::current_timestamp()))
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_comment_revision_comment_id")
+          .table(WlCommentRevision::Table)
+          .col(WlCommentRevision::CommentId)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(WlComment::Table)
+          .add_column(timestamp_null(WlComment::EditedAt))
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(WlComment::Table)
+          .drop_column(WlComment::EditedAt)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_table(Table::drop().table(WlCommentRevision::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlCommentRevision {
+  #[sea_orm(iden = "wl_CommentRevision")]
+  Table,
+  Id,
+  CommentId,
+  Comment,
+  UserId,
+  #[sea_orm(iden = "createdAt")]
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum WlComment {
+  #[sea_orm(iden = "wl_Comment")]
+  Table,
+  #[sea_orm(iden = "editedAt")]
+  EditedAt,
+}
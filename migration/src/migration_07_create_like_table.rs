@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WlLike::Table)
+          .if_not_exists()
+          .col(pk_auto(WlLike::Id).unsigned())
+          .col(integer(WlLike::CommentId))
+          .col(integer_null(WlLike::UserId))
+          .col(string_null(WlLike::Fingerprint))
+          .col(timestamp_null(WlLike::CreatedAt).default(Expr::current_timestamp()))
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_like_comment_id")
+          .table(WlLike::Table)
+          .col(WlLike::CommentId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(WlLike::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlLike {
+  #[sea_orm(iden = "wl_Like")]
+  Table,
+  Id,
+  CommentId,
+  UserId,
+  Fingerprint,
+  #[sea_orm(iden = "createdAt")]
+  CreatedAt,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WlReaction::Table)
+          .if_not_exists()
+          .col(pk_auto(WlReaction::Id).unsigned())
+          .col(integer(WlReaction::CommentId))
+          .col(string(WlReaction::Reaction))
+          .col(integer_null(WlReaction::UserId))
+          .col(string_null(WlReaction::Fingerprint))
+          .col(timestamp_null(WlReaction::CreatedAt).default(Expr::current_timestamp()))
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_reaction_comment_id")
+          .table(WlReaction::Table)
+          .col(WlReaction::CommentId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(WlReaction::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlReaction {
+  #[sea_orm(iden = "wl_Reaction")]
+  Table,
+  Id,
+  CommentId,
+  Reaction,
+  UserId,
+  Fingerprint,
+  #[sea_orm(iden = "createdAt")]
+  CreatedAt,
+}
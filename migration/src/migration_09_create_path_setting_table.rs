@@ -0,0 +1,48 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(WlPathSetting::Table)
+          .if_not_exists()
+          .col(pk_auto(WlPathSetting::Id).unsigned())
+          .col(string_uniq(WlPathSetting::Url))
+          .col(boolean(WlPathSetting::Closed).default(false))
+          .col(integer_null(WlPathSetting::AutoCloseDays))
+          .col(boolean_null(WlPathSetting::CommentAudit))
+          .col(timestamp_null(WlPathSetting::CreatedAt).default(Expr::current_timestamp()))
+          .col(timestamp_null(WlPathSetting::UpdatedAt).default(Expr::current_timestamp()))
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(WlPathSetting::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlPathSetting {
+  #[sea_orm(iden = "wl_PathSetting")]
+  Table,
+  Id,
+  Url,
+  Closed,
+  #[sea_orm(iden = "autoCloseDays")]
+  AutoCloseDays,
+  #[sea_orm(iden = "commentAudit")]
+  CommentAudit,
+  #[sea_orm(iden = "createdAt")]
+  CreatedAt,
+  #[sea_orm(iden = "updatedAt")]
+  UpdatedAt,
+}
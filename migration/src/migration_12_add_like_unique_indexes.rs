@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Concurrent requests could record the same like twice, keep the first one of each
+    let first_likes = Query::select()
+      .expr_as(Expr::col(WlLike::Id).min(), WlLike::Id)
+      .from(WlLike::Table)
+      .group_by_columns([WlLike::CommentId, WlLike::UserId, WlLike::Fingerprint])
+      .to_owned();
+    manager
+      .exec_stmt(
+        Query::delete()
+          .from_table(WlLike::Table)
+          .and_where(
+            Expr::col(WlLike::Id).not_in_subquery(
+              Query::select()
+                .column(WlLike::Id)
+                .from_subquery(first_likes, Alias::new("first_likes"))
+                .to_owned(),
+            ),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_like_comment_id_user_id")
+          .table(WlLike::Table)
+          .col(WlLike::CommentId)
+          .col(WlLike::UserId)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_like_comment_id_fingerprint")
+          .table(WlLike::Table)
+          .col(WlLike::CommentId)
+          .col(WlLike::Fingerprint)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_wl_like_comment_id_fingerprint")
+          .table(WlLike::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_wl_like_comment_id_user_id")
+          .table(WlLike::Table)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlLike {
+  #[sea_orm(iden = "wl_Like")]
+  Table,
+  Id,
+  CommentId,
+  UserId,
+  Fingerprint,
+}
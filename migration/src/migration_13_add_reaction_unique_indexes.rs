@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // Concurrent requests could record the same reaction twice, keep the first one of each
+    let first_reactions = Query::select()
+      .expr_as(Expr::col(WlReaction::Id).min(), WlReaction::Id)
+      .from(WlReaction::Table)
+      .group_by_columns([
+        WlReaction::CommentId,
+        WlReaction::Reaction,
+        WlReaction::UserId,
+        WlReaction::Fingerprint,
+      ])
+      .to_owned();
+    manager
+      .exec_stmt(
+        Query::delete()
+          .from_table(WlReaction::Table)
+          .and_where(
+            Expr::col(WlReaction::Id).not_in_subquery(
+              Query::select()
+                .column(WlReaction::Id)
+                .from_subquery(first_reactions, Alias::new("first_reactions"))
+                .to_owned(),
+            ),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_reaction_comment_id_reaction_user_id")
+          .table(WlReaction::Table)
+          .col(WlReaction::CommentId)
+          .col(WlReaction::Reaction)
+          .col(WlReaction::UserId)
+          .unique()
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx_wl_reaction_comment_id_reaction_fingerprint")
+          .table(WlReaction::Table)
+          .col(WlReaction::CommentId)
+          .col(WlReaction::Reaction)
+          .col(WlReaction::Fingerprint)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_wl_reaction_comment_id_reaction_fingerprint")
+          .table(WlReaction::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx_wl_reaction_comment_id_reaction_user_id")
+          .table(WlReaction::Table)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlReaction {
+  #[sea_orm(iden = "wl_Reaction")]
+  Table,
+  Id,
+  CommentId,
+  Reaction,
+  UserId,
+  Fingerprint,
+}
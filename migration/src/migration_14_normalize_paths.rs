@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use percent_encoding::percent_decode_str;
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+/// Canonical form of an article path when this migration was written, a copy of the app's
+/// `normalize_path` so that later changes to it do not change what this migration did
+fn normalize_path(path: &str, ignore_case: bool) -> String {
+  let mut path = path.trim();
+  if !path.starts_with('/')
+    && let Some((_, rest)) = path.split_once("://")
+  {
+    path = rest.find('/').map_or("/", |start| &rest[start..]);
+  }
+  let path = path.split(['?', '#']).next().unwrap_or_default();
+  let decoded = percent_decode_str(path)
+    .decode_utf8()
+    .map_or_else(|_| path.to_string(), |decoded| decoded.into_owned());
+  let mut segments: Vec<&str> = decoded
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect();
+  if matches!(segments.last(), Some(&("index.html" | "index.htm"))) {
+    segments.pop();
+  }
+  let path = format!("/{}", segments.join("/"));
+  if ignore_case {
+    path.to_lowercase()
+  } else {
+    path
+  }
+}
+
+const COUNTER_VALUES: [WlCounter; 10] = [
+  WlCounter::Time,
+  WlCounter::Reaction0,
+  WlCounter::Reaction1,
+  WlCounter::Reaction2,
+  WlCounter::Reaction3,
+  WlCounter::Reaction4,
+  WlCounter::Reaction5,
+  WlCounter::Reaction6,
+  WlCounter::Reaction7,
+  WlCounter::Reaction8,
+];
+
+/// Rewrite the stored paths of comments, counters and path settings into their canonical form,
+/// once. Counters that end up on the same page are added up, and of path settings that do the
+/// one already at the canonical path, or else the latest, is kept. `PATH_IGNORE_CASE` is read
+/// as it is when the migration runs: changing it later leaves the stored paths alone.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let ignore_case = std::env::var("PATH_IGNORE_CASE").is_ok_and(|value| value == "true");
+    let normalize = |path: &str| normalize_path(path, ignore_case);
+    let db = manager.get_connection();
+    let backend = db.get_database_backend();
+
+    let comment_urls = db
+      .query_all(
+        backend.build(
+          Query::select()
+            .distinct()
+            .column(WlComment::Url)
+            .from(WlComment::Table),
+        ),
+      )
+      .await?;
+    for row in comment_urls {
+      let Some(url) = row.try_get::<Option<String>>("", "url")? else {
+        continue;
+      };
+      let canonical = normalize(&url);
+      if canonical != url {
+        db.execute(
+          backend.build(
+            Query::update()
+              .table(WlComment::Table)
+              .value(WlComment::Url, canonical)
+              .and_where(Expr::col(WlComment::Url).eq(url)),
+          ),
+        )
+        .await?;
+      }
+    }
+
+    let counters = db
+      .query_all(
+        backend.build(
+          Query::select()
+            .column(WlCounter::Id)
+            .column(WlCounter::Url)
+            .columns(COUNTER_VALUES)
+            .from(WlCounter::Table)
+            .order_by(WlCounter::Id, Order::Asc),
+        ),
+      )
+      .await?;
+    let mut pages: BTreeMap<String, Vec<(u32, String, Vec<Option<i32>>)>> = BTreeMap::new();
+    for row in counters {
+      let url: String = row.try_get("", "url")?;
+      let values = COUNTER_VALUES
+        .iter()
+        .map(|column| row.try_get("", &column.to_string()))
+        .collect::<Result<_, _>>()?;
+      pages
+        .entry(normalize(&url))
+        .or_default()
+        .push((row.try_get("", "id")?, url, values));
+    }
+    for (url, counters) in pages {
+      if let [(_, stored, _)] = counters.as_slice()
+        && *stored == url
+      {
+        continue;
+      }
+      let (kept, _, _) = counters
+        .iter()
+        .find(|(_, stored, _)| *stored == url)
+        .unwrap_or(&counters[0]);
+      let mut update = Query::update();
+      update
+        .table(WlCounter::Table)
+        .value(WlCounter::Url, url.clone())
+        .and_where(Expr::col(WlCounter::Id).eq(*kept));
+      for (index, column) in COUNTER_VALUES.into_iter().enumerate() {
+        let sum = counters
+          .iter()
+          .filter_map(|(_, _, values)| values[index])
+          .reduce(|a, b| a + b);
+        update.value(column, sum);
+      }
+      db.execute(
+        backend.build(
+          Query::delete()
+            .from_table(WlCounter::Table)
+            .and_where(Expr::col(WlCounter::Id).ne(*kept))
+            .and_where(Expr::col(WlCounter::Id).is_in(counters.iter().map(|(id, _, _)| *id))),
+        ),
+      )
+      .await?;
+      db.execute(backend.build(&update)).await?;
+    }
+
+    let settings = db
+      .query_all(
+        backend.build(
+          Query::select()
+            .column(WlPathSetting::Id)
+            .column(WlPathSetting::Url)
+            .from(WlPathSetting::Table)
+            .order_by(WlPathSetting::Id, Order::Desc),
+        ),
+      )
+      .await?;
+    let mut pages: BTreeMap<String, Vec<(u32, String)>> = BTreeMap::new();
+    for row in settings {
+      let url: String = row.try_get("", "url")?;
+      pages
+        .entry(normalize(&url))
+        .or_default()
+        .push((row.try_get("", "id")?, url));
+    }
+    for (url, settings) in pages {
+      let (kept, stored) = settings
+        .iter()
+        .find(|(_, stored)| *stored == url)
+        .unwrap_or(&settings[0]);
+      if settings.len() > 1 {
+        db.execute(
+          backend.build(
+            Query::delete()
+              .from_table(WlPathSetting::Table)
+              .and_where(Expr::col(WlPathSetting::Id).ne(*kept))
+              .and_where(Expr::col(WlPathSetting::Id).is_in(settings.iter().map(|(id, _)| *id))),
+          ),
+        )
+        .await?;
+      }
+      if *stored != url {
+        db.execute(
+          backend.build(
+            Query::update()
+              .table(WlPathSetting::Table)
+              .value(WlPathSetting::Url, url.clone())
+              .and_where(Expr::col(WlPathSetting::Id).eq(*kept)),
+          ),
+        )
+        .await?;
+      }
+    }
+    Ok(())
+  }
+
+  async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+    // The original spellings of the paths are gone
+    Ok(())
+  }
+}
+
+#[derive(DeriveIden)]
+enum WlComment {
+  #[sea_orm(iden = "wl_Comment")]
+  Table,
+  Url,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum WlCounter {
+  #[sea_orm(iden = "wl_Counter")]
+  Table,
+  Id,
+  Url,
+  Time,
+  Reaction0,
+  Reaction1,
+  Reaction2,
+  Reaction3,
+  Reaction4,
+  Reaction5,
+  Reaction6,
+  Reaction7,
+  Reaction8,
+}
+
+#[derive(DeriveIden)]
+enum WlPathSetting {
+  #[sea_orm(iden = "wl_PathSetting")]
+  Table,
+  Id,
+  Url,
+}
@@ -9,13 +9,17 @@ use crate::{
   components::{
     article,
     comment::{self},
-    migration,
+    emoji, migration, path_setting, shadow_ban, stats, trust,
     ui::{self, handler::ui_page},
     user,
   },
   config::EnvConfig,
   error::AppError,
-  helpers::ip::Ip2Region,
+  helpers::{
+    emoji::EmojiRegistry,
+    ip::Ip2Region,
+    markdown::{MarkdownOptions, SanitizePolicy},
+  },
   middlewares::SecureDomians,
   migration::migrate,
   repository::RepositoryManager,
@@ -66,33 +70,76 @@ impl RateLimiter {
   }
 }
 
+/// Path, page, page size and sort order of a cached comment page
+type CommentCacheKey = (String, i32, i32, String);
+
+/// Root comment ids of a path in `sort=hot` order, keyed by path and whether the view is an
+/// administrator's, with when they were ranked
+type HotRankings = HashMap<(String, bool), (Instant, Vec<u32>)>;
+
+/// How long a `sort=hot` ranking is reused, since scores decay with time
+const HOT_RANKING_TTL: Duration = Duration::from_secs(60);
+
 #[derive(Clone)]
+/// Pages of comments keyed by path, page, page size and sort order
 pub struct CommentCache {
-  pub cache: Arc<Mutex<HashMap<(String, i32), Value>>>,
+  pub cache: Arc<Mutex<HashMap<CommentCacheKey, Value>>>,
+  hot: Arc<Mutex<HotRankings>>,
 }
 
 impl CommentCache {
   fn new() -> Self {
     CommentCache {
       cache: Arc::new(Mutex::new(HashMap::new())),
+      hot: Arc::new(Mutex::new(HashMap::new())),
     }
   }
 
-  pub fn get(&self, path: String, page: i32) -> Option<Value> {
-    self.cache.lock().unwrap().get(&(path, page)).cloned()
+  pub fn get_hot(&self, path: &str, is_admin: bool) -> Option<Vec<u32>> {
+    self
+      .hot
+      .lock()
+      .unwrap()
+      .get(&(path.to_string(), is_admin))
+      .filter(|(ranked_at, _)| ranked_at.elapsed() < HOT_RANKING_TTL)
+      .map(|(_, ids)| ids.clone())
+  }
+
+  pub fn insert_hot(&mut self, path: &str, is_admin: bool, ids: Vec<u32>) {
+    self
+      .hot
+      .lock()
+      .unwrap()
+      .insert((path.to_string(), is_admin), (Instant::now(), ids));
+  }
+
+  pub fn get(&self, path: String, page: i32, page_size: i32, sort_by: String) -> Option<Value> {
+    self
+      .cache
+      .lock()
+      .unwrap()
+      .get(&(path, page, page_size, sort_by))
+      .cloned()
   }
 
-  pub fn insert(&mut self, path: String, page: i32, data: Value) {
-    self.cache.lock().unwrap().insert((path, page), data);
+  pub fn insert(&mut self, path: String, page: i32, page_size: i32, sort_by: String, data: Value) {
+    self
+      .cache
+      .lock()
+      .unwrap()
+      .insert((path, page, page_size, sort_by), data);
   }
 
   pub fn invalidate(&self, path: &str) {
     let mut cache = self.cache.lock().unwrap();
-    cache.retain(|(old_path, _), _| old_path != path);
+    cache.retain(|(old_path, ..), _| old_path != path);
+    let mut hot = self.hot.lock().unwrap();
+    hot.retain(|(old_path, _), _| old_path != path);
   }
 
   pub fn clear(&self) {
     self.cache.lock().unwrap().clear();
+    self.hot.lock().unwrap().clear();
   }
 }
 
@@ -103,8 +150,17 @@ pub struct AppState {
   pub jwt_token: String,
   pub levels: Option<String>,
   pub comment_audit: bool,
+  pub trusted_comment_count: u64,
+  pub trusted_spam_window: i64,
+  pub report_threshold: u64,
+  pub edit_grace_period: i64,
+  pub hot_decay: f64,
+  pub inline_reply_limit: u64,
+  pub path_ignore_case: bool,
   pub login: String,
   pub forbidden_words: Vec<String>,
+  pub comment_reactions: Vec<String>,
+  pub markdown: MarkdownOptions,
   pub disable_useragent: bool,
   pub disable_region: bool,
   pub comment_cache: Arc<Mutex<CommentCache>>,
@@ -124,8 +180,13 @@ pub fn config_app(cfg: &mut ServiceConfig) {
     web::scope("/api")
       .configure(article::config)
       .configure(comment::config)
+      .configure(emoji::config)
       .configure(user::config)
       .configure(migration::config)
+      .configure(shadow_ban::config)
+      .configure(stats::config)
+      .configure(trust::config)
+      .configure(path_setting::config)
       .route("/health", web::get().to(health_check)),
   );
   cfg.route("/ui", web::get().to(ui_page));
@@ -134,6 +195,22 @@ pub fn config_app(cfg: &mut ServiceConfig) {
   cfg.route("/", web::get().to(health_check));
 }
 
+/// Purge comments that stayed in the trash longer than the retention period, once an hour
+fn spawn_trash_purge(repo: RepositoryManager, retention_days: u64) {
+  actix_web::rt::spawn(async move {
+    let mut interval = actix_web::rt::time::interval(Duration::from_secs(3600));
+    loop {
+      interval.tick().await;
+      let before = helpers::time::utc_now() - chrono::Duration::days(retention_days as i64);
+      match repo.comment().purge_trash(before).await {
+        Ok(0) => {}
+        Ok(count) => info!("Purged {count} comments from the trash"),
+        Err(err) => tracing::error!("{:#?}", err),
+      }
+    }
+  });
+}
+
 pub async fn start() -> Result<(), AppError> {
   let EnvConfig {
     workers,
@@ -145,14 +222,31 @@ pub async fn start() -> Result<(), AppError> {
     akismet_key,
     ipqps,
     comment_audit,
+    trusted_comment_count,
+    trusted_spam_window,
+    report_threshold,
+    edit_grace_period,
+    hot_decay,
+    inline_reply_limit,
+    path_ignore_case,
+    trash_retention_days,
     login,
     forbidden_words,
+    comment_reactions,
+    markdown_extensions,
+    markdown_highlight,
+    sanitize_allow_tags,
+    sanitize_deny_tags,
+    sanitize_allow_attributes,
+    link_redirect,
+    emoji_dir,
     disable_useragent,
     disable_region,
     ip2region_db,
     secure_domains,
     site_name,
     site_url,
+    server_url,
     ..
   } = EnvConfig::load_env()?;
   let conn = migrate(&database_url).await?;
@@ -169,13 +263,47 @@ pub async fn start() -> Result<(), AppError> {
   } else {
     tracing::info!("The ip region cannot be obtained because xdb is not provided!")
   }
+  let emoji = match &emoji_dir {
+    Some(emoji_dir) => EmojiRegistry::load(emoji_dir, server_url.as_deref().unwrap_or_default())
+      .unwrap_or_else(|err| {
+        tracing::error!("Emoji packs cannot be loaded from {emoji_dir}: {err}");
+        EmojiRegistry::default()
+      }),
+    None => EmojiRegistry::default(),
+  };
+  if trash_retention_days > 0 {
+    spawn_trash_purge(RepositoryManager::new(conn.clone()), trash_retention_days);
+  }
   let state = AppState {
     repo: RepositoryManager::new(conn.clone()),
     jwt_token,
     levels,
     login,
     comment_audit,
+    trusted_comment_count,
+    trusted_spam_window,
+    report_threshold,
+    edit_grace_period,
+    hot_decay,
+    inline_reply_limit,
+    path_ignore_case,
     forbidden_words,
+    comment_reactions: comment_reactions
+      .into_iter()
+      .filter(|reaction| !reaction.is_empty())
+      .collect(),
+    markdown: MarkdownOptions {
+      highlight: markdown_highlight,
+      policy: SanitizePolicy::new(
+        sanitize_allow_tags,
+        sanitize_deny_tags,
+        sanitize_allow_attributes,
+        link_redirect,
+        &site_url,
+      ),
+      emoji: Arc::new(emoji),
+      ..MarkdownOptions::from_names(&markdown_extensions)
+    },
     disable_useragent,
     disable_region,
     ip2region,
@@ -201,6 +329,11 @@ pub async fn start() -> Result<(), AppError> {
         .wrap(Cors::permissive())
         .app_data(web::Data::new(state.clone()))
         .configure(config_app)
+        .configure(|cfg| {
+          if let Some(emoji_dir) = &emoji_dir {
+            emoji::files(cfg, emoji_dir);
+          }
+        })
     })
     .bind((host, port))?
     .workers(workers)
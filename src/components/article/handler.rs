@@ -2,10 +2,11 @@ use crate::prelude::*;
 use crate::{
   app::AppState,
   components::article::{model::*, service},
+  helpers::header::extract_token,
 };
 
 use actix_web::{
-  HttpResponse, get, post,
+  HttpRequest, HttpResponse, get, post,
   web::{Data, Json, Query},
 };
 
@@ -36,3 +37,16 @@ async fn update_article(
     .await
     .into_http_response(Some(&lang))
 }
+
+#[post("/article/move")]
+async fn move_article(
+  req: HttpRequest,
+  data: Data<AppState>,
+  query: Query<MoveArticleQuery>,
+  body: Json<MoveArticleBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(MoveArticleQuery { lang }) = query;
+  service::move_article(&data, extract_token(&req)?, body.into_inner())
+    .await
+    .into_http_response(Some(&lang))
+}
@@ -7,4 +7,5 @@ use actix_web::web::ServiceConfig;
 pub fn config(cfg: &mut ServiceConfig) {
   cfg.service(handler::get_article);
   cfg.service(handler::update_article);
+  cfg.service(handler::move_article);
 }
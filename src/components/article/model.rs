@@ -18,3 +18,27 @@ pub struct UpdateArticleBody {
 pub struct UpdateArticleQuery {
   pub lang: String,
 }
+
+#[derive(Deserialize)]
+pub struct PathMapping {
+  pub from: String,
+  pub to: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveArticleBody {
+  pub from: Option<String>,
+  pub to: Option<String>,
+  /// Several moves at once
+  pub mappings: Option<Vec<PathMapping>>,
+  /// Several moves at once, one `old,new` pair per line
+  pub csv: Option<String>,
+  #[serde(default)]
+  pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveArticleQuery {
+  pub lang: String,
+}
@@ -3,7 +3,13 @@ use sea_orm::{IntoActiveModel, Set};
 use serde_json::{Value, json};
 
 use crate::prelude::*;
-use crate::{app::AppState, entities::wl_counter};
+use crate::{
+  app::AppState,
+  components::article::model::{MoveArticleBody, PathMapping},
+  entities::wl_counter,
+  helpers::{auth::verify_admin, path::normalize_path},
+  repository::CounterMove,
+};
 
 pub async fn get_article(
   state: &AppState,
@@ -13,7 +19,8 @@ pub async fn get_article(
   let mut data = vec![];
   if r#type == "time" {
     for path in path.split(',') {
-      match state.repo.counter().get_counter(path).await? {
+      let path = normalize_path(path, state.path_ignore_case);
+      match state.repo.counter().get_counter(&path).await? {
         Some(counter) => {
           data.push(json!({"time": counter.time}));
         }
@@ -22,7 +29,12 @@ pub async fn get_article(
         }
       }
     }
-  } else if let Some(counter) = state.repo.counter().get_counter(&path).await? {
+  } else if let Some(counter) = state
+    .repo
+    .counter()
+    .get_counter(&normalize_path(&path, state.path_ignore_case))
+    .await?
+  {
     data.push(json!({
       "reaction0": counter.reaction0,
       "reaction1": counter.reaction1,
@@ -41,6 +53,7 @@ pub async fn update_article(
   path: String,
   r#type: String,
 ) -> ServiceResult<Vec<wl_counter::Model>> {
+  let path = normalize_path(&path, state.path_ignore_case);
   let mut data = vec![];
   if r#type == "time" {
     match state.repo.counter().get_counter(&path).await? {
@@ -118,3 +131,88 @@ pub async fn update_article(
   }
   Ok(data)
 }
+
+/// Pairs of old and new paths from a CSV text, a header line naming the columns is skipped
+fn parse_csv_mappings(csv: &str) -> ServiceResult<Vec<PathMapping>> {
+  let mut mappings = vec![];
+  for (index, line) in csv.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() {
+      continue;
+    }
+    let (from, to) = line.split_once(',').ok_or(AppError::Error)?;
+    let (from, to) = (from.trim().trim_matches('"'), to.trim().trim_matches('"'));
+    if index == 0 && !from.starts_with('/') && !from.contains("://") {
+      continue;
+    }
+    mappings.push(PathMapping {
+      from: from.to_string(),
+      to: to.to_string(),
+    });
+  }
+  Ok(mappings)
+}
+
+/// Move the comments and counters of renamed articles to their new paths, merging them into
+/// whatever the new path already has
+pub async fn move_article(
+  state: &AppState,
+  token: String,
+  body: MoveArticleBody,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let MoveArticleBody {
+    from,
+    to,
+    mappings,
+    csv,
+    dry_run,
+  } = body;
+  let mut pairs: Vec<PathMapping> = vec![];
+  if let (Some(from), Some(to)) = (from, to) {
+    pairs.push(PathMapping { from, to });
+  }
+  pairs.extend(mappings.into_iter().flatten());
+  if let Some(csv) = csv {
+    pairs.extend(parse_csv_mappings(&csv)?);
+  }
+  let mappings: Vec<(String, String)> = pairs
+    .into_iter()
+    .map(|PathMapping { from, to }| {
+      (
+        normalize_path(&from, state.path_ignore_case),
+        normalize_path(&to, state.path_ignore_case),
+      )
+    })
+    .collect();
+  if mappings.is_empty() {
+    return Err(AppError::Error);
+  }
+  let moves = state.repo.article().move_paths(&mappings, dry_run).await?;
+  if !dry_run {
+    let cache = state.comment_cache.lock().unwrap();
+    for (from, to) in &mappings {
+      cache.invalidate(from);
+      cache.invalidate(to);
+    }
+  }
+  let moves: Vec<Value> = moves
+    .into_iter()
+    .map(|path_move| {
+      json!({
+        "from": path_move.from,
+        "to": path_move.to,
+        "comments": path_move.comments,
+        "counter": match path_move.counter {
+          CounterMove::None => "none",
+          CounterMove::Rename => "rename",
+          CounterMove::Merge => "merge",
+        },
+      })
+    })
+    .collect();
+  Ok(json!({
+    "dryRun": dry_run,
+    "moves": moves,
+  }))
+}
@@ -1,48 +1,97 @@
 use actix_web::{
-  HttpRequest, HttpResponse, delete, get, post, put,
+  HttpRequest, HttpResponse,
+  cookie::{Cookie, SameSite, time::Duration as CookieDuration},
+  delete, get, post, put,
   web::{Data, Json, Path, Query},
 };
 use helpers::jwt;
+use serde_json::json;
 
 use crate::{
   app::AppState,
   components::comment::{model::*, service},
   config::EnvConfig,
-  helpers::header::{extract_ip, extract_token},
+  helpers::header::{extract_edit_token, extract_ip, extract_token, extract_user_agent},
   prelude::{AppError, Response},
   traits::IntoHttpResponse,
 };
 
+/// A login token wins over an edit token when both are sent
+fn extract_editor(req: &HttpRequest, state: &AppState) -> Result<CommentEditor, AppError> {
+  match extract_token(req) {
+    Ok(token) => Ok(CommentEditor::User(
+      jwt::verify::<String>(&token, &state.jwt_token)?.claims.data,
+    )),
+    Err(err) => extract_edit_token(req)
+      .map(CommentEditor::Author)
+      .ok_or(err),
+  }
+}
+
 #[get("/comment")]
 async fn get_comment_info(
   req: HttpRequest,
   state: Data<AppState>,
   query: Query<GetCommentQuery>,
 ) -> Result<HttpResponse, AppError> {
-  let Query(GetCommentQuery {
+  let GetCommentQuery {
     lang,
     path,
     page_size,
     page,
+    cursor,
     sort_by,
-    r#type: _,
-    owner,
-    status,
-    keyword,
-  }) = query.clone();
+    r#type,
+    count,
+    url,
+    ..
+  } = query.clone().into_inner();
+  match r#type.as_deref() {
+    Some("recent") => {
+      return service::get_recent_comments(&state, count.unwrap_or(10))
+        .await
+        .into_http_response(Some(&lang));
+    }
+    Some("count") => {
+      let paths = url
+        .or(path)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(String::from)
+        .collect();
+      return service::get_path_comment_counts(&state, paths)
+        .await
+        .into_http_response(Some(&lang));
+    }
+    _ => {}
+  }
   if let Some(path) = path {
     let fields = query.validate_by_path();
     if fields.is_err() {
       return Response::<()>::new_error(AppError::Error, Some(&lang));
     }
     let token = extract_token(&req);
+    let identity = req
+      .cookie(IDENTITY_COOKIE)
+      .map(|cookie| cookie.value().to_string());
+    let position = match cursor {
+      Some(cursor) => CommentPosition::Cursor(cursor),
+      None => CommentPosition::Page(page),
+    };
     service::get_comment_info(
       &state,
       path,
-      page,
+      position,
       page_size.unwrap(),
       sort_by.unwrap(),
-      token,
+      CommentViewer {
+        token,
+        identity,
+        ip: extract_ip(&req),
+        ua: extract_user_agent(&req),
+      },
     )
     .await
     .into_http_response(Some(&lang))
@@ -60,16 +109,9 @@ async fn get_comment_info(
     if !is {
       return Response::<()>::new_error(AppError::Unauthorized, Some(&lang));
     }
-    service::get_comment_info_by_admin(
-      &state,
-      owner.unwrap(),
-      email,
-      keyword.unwrap(),
-      status.unwrap(),
-      page,
-    )
-    .await
-    .into_http_response(Some(&lang))
+    service::get_comment_info_by_admin(&state, email, query.into_inner())
+      .await
+      .into_http_response(Some(&lang))
   }
 }
 
@@ -153,6 +195,7 @@ async fn create_comment(
       Some(&lang),
     ));
   }
+  let anonymous = matches!(user_type, UserType::Anonymous);
   match service::create_comment(
     &state,
     comment,
@@ -170,6 +213,74 @@ async fn create_comment(
   )
   .await
   {
+    Ok(mut data) => {
+      let mut response = HttpResponse::Ok();
+      // The commenter must not be able to tell that they are shadow-banned
+      if data["status"] == "shadowban" {
+        data["status"] = json!("approved");
+      }
+      if anonymous
+        && let Some(id) = data["objectId"].as_u64()
+        && let Some(identity) = service::sign_identity(
+          &state,
+          req.cookie(IDENTITY_COOKIE).as_ref().map(Cookie::value),
+          id as u32,
+        )
+      {
+        response.cookie(
+          Cookie::build(IDENTITY_COOKIE, identity)
+            .path("/")
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::None)
+            .max_age(CookieDuration::days(365))
+            .finish(),
+        );
+      }
+      response.json(Response::success(Some(data)))
+    }
+    Err(err) => HttpResponse::Ok().json(Response::<()>::error(err, Some(&lang))),
+  }
+}
+
+#[post("/comment/preview")]
+async fn preview_comment(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<CreateCommentQuery>,
+  body: Json<PreviewCommentBody>,
+) -> HttpResponse {
+  let Query(CreateCommentQuery { lang }) = query;
+  let Json(PreviewCommentBody { comment, url }) = body;
+  let is_admin = match extract_token(&req) {
+    Ok(token) => match jwt::verify::<String>(&token, &state.jwt_token) {
+      Ok(verified_token) => state
+        .repo
+        .user()
+        .is_admin_user(&verified_token.claims.data)
+        .await
+        .unwrap_or(false),
+      Err(_) => {
+        return HttpResponse::Ok().json(Response::<()>::error(AppError::Unauthorized, Some(&lang)));
+      }
+    },
+    _ if &state.login == "force" => {
+      return HttpResponse::Ok().json(Response::<()>::error(AppError::Unauthorized, Some(&lang)));
+    }
+    _ => false,
+  };
+  // Previews are limited like posting, in a bucket of their own so they do not use it up
+  if !is_admin
+    && !state
+      .rate_limiter
+      .check_and_update(&format!("preview:{}", extract_ip(&req)), 1)
+  {
+    return HttpResponse::Ok().json(Response::<()>::error(
+      AppError::FrequencyLimited,
+      Some(&lang),
+    ));
+  }
+  match service::preview_comment(&state, comment, url).await {
     Ok(data) => HttpResponse::Ok().json(Response::success(Some(data))),
     Err(err) => HttpResponse::Ok().json(Response::<()>::error(err, Some(&lang))),
   }
@@ -182,13 +293,81 @@ pub async fn delete_comment(
   path: Path<u32>,
 ) -> Result<HttpResponse, AppError> {
   let id = path.into_inner();
-  let token = extract_token(&req)?;
-  match service::delete_comment(&state, id, token).await {
+  let editor = extract_editor(&req, &state)?;
+  match service::delete_comment(&state, id, editor).await {
     Ok(_) => Response::<()>::new_success(None),
     Err(err) => Response::<()>::new_error(err, None),
   }
 }
 
+#[post("/comment/{id}/restore")]
+async fn restore_comment(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<RestoreCommentQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(RestoreCommentQuery { lang }) = query;
+  service::restore_comment(&state, path.into_inner(), extract_token(&req)?)
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[get("/comment/mentions")]
+async fn get_comment_mentions(
+  state: Data<AppState>,
+  query: Query<CommentMentionsQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(CommentMentionsQuery { lang, path }) = query;
+  service::get_thread_mentions(&state, path)
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[get("/comment/{id}/replies")]
+async fn get_comment_replies(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<CommentRepliesQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(CommentRepliesQuery {
+    lang,
+    page,
+    page_size,
+  }) = query;
+  let identity = req
+    .cookie(IDENTITY_COOKIE)
+    .map(|cookie| cookie.value().to_string());
+  service::get_comment_replies(
+    &state,
+    path.into_inner(),
+    page,
+    page_size.unwrap_or(10),
+    CommentViewer {
+      token: extract_token(&req),
+      identity,
+      ip: extract_ip(&req),
+      ua: extract_user_agent(&req),
+    },
+  )
+  .await
+  .into_http_response(Some(&lang))
+}
+
+#[get("/comment/{id}/revisions")]
+async fn get_comment_revisions(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<CommentRevisionsQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(CommentRevisionsQuery { lang }) = query;
+  service::get_comment_revisions(&state, path.into_inner(), extract_token(&req)?)
+    .await
+    .into_http_response(Some(&lang))
+}
+
 #[put("/comment/{id}")]
 async fn update_comment(
   req: HttpRequest,
@@ -206,52 +385,114 @@ async fn update_comment(
     ua,
     url,
     sticky,
+    locked,
   }) = body;
   let id: u32 = path.into_inner();
-  if like.is_some() {
-    match service::update_comment(
+  // Likes sent through the generic update only ever touch the like
+  if let Some(like) = like {
+    return match service::like_comment(
       &state,
-      String::new(),
       id,
-      status,
       like,
-      comment,
-      link,
-      mail,
-      nick,
-      ua,
-      url,
-      sticky,
+      extract_token(&req).ok(),
+      extract_ip(&req),
+      extract_user_agent(&req),
     )
     .await
     {
-      Ok(data) => return HttpResponse::Ok().json(Response::success(Some(data))),
-      Err(err) => return HttpResponse::Ok().json(Response::<()>::error(err, None)),
-    }
+      Ok(data) => HttpResponse::Ok().json(Response::success(Some(data))),
+      Err(err) => HttpResponse::Ok().json(Response::<()>::error(err, None)),
+    };
   }
-  match extract_token(&req) {
-    Ok(token) => match jwt::verify::<String>(&token, &state.jwt_token) {
-      Ok(data) => match service::update_comment(
-        &state,
-        data.claims.data,
-        id,
-        status,
-        like,
-        comment,
-        link,
-        mail,
-        nick,
-        ua,
-        url,
-        sticky,
-      )
-      .await
-      {
-        Ok(data) => HttpResponse::Ok().json(Response::success(Some(data))),
-        Err(err) => HttpResponse::Ok().json(Response::<()>::error(err, None)),
-      },
-      Err(_) => HttpResponse::Ok().json(Response::<()>::error(AppError::Unauthorized, None)),
-    },
-    _ => HttpResponse::Ok().json(Response::<()>::error(AppError::Unauthorized, None)),
+  let editor = match extract_editor(&req, &state) {
+    Ok(editor) => editor,
+    Err(_) => return HttpResponse::Ok().json(Response::<()>::error(AppError::Unauthorized, None)),
+  };
+  match service::update_comment(
+    &state, editor, id, status, comment, link, mail, nick, ua, url, sticky, locked,
+  )
+  .await
+  {
+    Ok(data) => HttpResponse::Ok().json(Response::success(Some(data))),
+    Err(err) => HttpResponse::Ok().json(Response::<()>::error(err, None)),
   }
 }
+
+#[post("/comment/{id}/like")]
+async fn like_comment(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<LikeCommentQuery>,
+  body: Json<LikeCommentBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(LikeCommentQuery { lang }) = query;
+  service::like_comment(
+    &state,
+    path.into_inner(),
+    body.like.unwrap_or(true),
+    extract_token(&req).ok(),
+    extract_ip(&req),
+    extract_user_agent(&req),
+  )
+  .await
+  .into_http_response(Some(&lang))
+}
+
+#[post("/comment/{id}/reaction")]
+async fn react_comment(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<ReactCommentQuery>,
+  body: Json<ReactCommentBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(ReactCommentQuery { lang }) = query;
+  let Json(ReactCommentBody { reaction, react }) = body;
+  service::react_comment(
+    &state,
+    path.into_inner(),
+    reaction,
+    react.unwrap_or(true),
+    extract_token(&req).ok(),
+    extract_ip(&req),
+    extract_user_agent(&req),
+  )
+  .await
+  .into_http_response(Some(&lang))
+}
+
+#[post("/comment/{id}/report")]
+async fn report_comment(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<ReportCommentQuery>,
+  body: Json<ReportCommentBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(ReportCommentQuery { lang }) = query;
+  let Json(ReportCommentBody { reason }) = body;
+  service::report_comment(
+    &state,
+    path.into_inner(),
+    reason,
+    extract_token(&req).ok(),
+    extract_ip(&req),
+    lang.clone(),
+  )
+  .await
+  .into_http_response(Some(&lang))
+}
+
+#[post("/comment/bulk")]
+async fn bulk_comment(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<BulkCommentQuery>,
+  body: Json<BulkCommentBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(BulkCommentQuery { lang }) = query;
+  service::bulk_comment(&state, extract_token(&req)?, body.0)
+    .await
+    .into_http_response(Some(&lang))
+}
@@ -7,6 +7,15 @@ use actix_web::web::ServiceConfig;
 pub fn config(cfg: &mut ServiceConfig) {
   cfg.service(handler::get_comment_info);
   cfg.service(handler::create_comment);
+  cfg.service(handler::preview_comment);
   cfg.service(handler::delete_comment);
+  cfg.service(handler::restore_comment);
+  cfg.service(handler::get_comment_revisions);
+  cfg.service(handler::get_comment_replies);
+  cfg.service(handler::get_comment_mentions);
   cfg.service(handler::update_comment);
+  cfg.service(handler::like_comment);
+  cfg.service(handler::react_comment);
+  cfg.service(handler::report_comment);
+  cfg.service(handler::bulk_comment);
 }
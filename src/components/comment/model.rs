@@ -1,12 +1,37 @@
+use std::collections::BTreeMap;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use sea_orm::Set;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
   entities::wl_comment,
-  helpers::{avatar::get_avatar, ip::Ip2Region, markdown::render_md_to_html, ua},
+  error::AppError,
+  helpers::{
+    avatar::get_avatar,
+    ip::Ip2Region,
+    markdown::{MarkdownOptions, render_comment},
+    mention::Commenter,
+    ua,
+  },
+  repository::RootCursor,
 };
 
+/// Signed cookie given to every anonymous commenter, so that one who is shadow banned keeps
+/// seeing their comments without being told about the ban
+pub const IDENTITY_COOKIE: &str = "wl_identity";
+
+/// How many of their latest comments an identity cookie remembers
+pub const IDENTITY_COMMENT_LIMIT: usize = 50;
+
+/// Claims of the `IDENTITY_COOKIE`
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Identity {
+  /// Comments posted by the holder of the cookie
+  pub comments: Vec<u32>,
+}
+
 #[derive(Serialize, Clone)]
 pub struct DataEntry {
   pub status: String,
@@ -32,10 +57,26 @@ pub struct DataEntry {
   pub level: Option<usize>,
   pub label: Option<String>,
   pub sticky: Option<i8>,
+  /// Replies to this comment or below it are refused
+  pub locked: bool,
   pub addr: Option<String>,
   pub children: Vec<DataEntry>,
+  /// Number of replies, of which only the first `INLINE_REPLY_LIMIT` are in `children`
+  #[serde(rename = "replyCount", skip_serializing_if = "Option::is_none")]
+  pub reply_count: Option<u64>,
+  #[serde(rename = "hasMore", skip_serializing_if = "Option::is_none")]
+  pub has_more: Option<bool>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub reply_user: Option<Value>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reports: Option<Vec<String>>,
+  #[serde(rename = "deletedAt", skip_serializing_if = "Option::is_none")]
+  pub deleted_at: Option<i64>,
+  pub edited: bool,
+  #[serde(rename = "editedAt")]
+  pub edited_at: Option<i64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reactions: Option<BTreeMap<String, i64>>,
 }
 
 fn is_strictly_increasing(nums: &[usize]) -> bool {
@@ -73,6 +114,8 @@ pub fn build_data_entry(
   ip2region: &Option<Ip2Region>,
   disable_useragent: bool,
   disable_region: bool,
+  markdown: &MarkdownOptions,
+  commenters: &[Commenter],
 ) -> DataEntry {
   let (browser, os) = if disable_useragent {
     ("".to_string(), "".to_string())
@@ -91,7 +134,7 @@ pub fn build_data_entry(
     None
   };
   let safe_html = if let Some(ref comment_text) = comment.comment {
-    Some(ammonia::clean(&render_md_to_html(comment_text)))
+    Some(render_comment(comment_text, markdown, commenters))
   } else {
     Some("".to_string())
   };
@@ -117,9 +160,17 @@ pub fn build_data_entry(
     level,
     label: None,
     sticky: comment.sticky,
+    locked: comment.locked,
     addr,
     children: vec![],
+    reply_count: None,
+    has_more: None,
     reply_user: None,
+    reports: None,
+    deleted_at: comment.deleted_at.map(|time| time.timestamp_millis()),
+    edited: comment.edited_at.is_some(),
+    edited_at: comment.edited_at.map(|time| time.timestamp_millis()),
+    reactions: None,
   }
 }
 
@@ -129,12 +180,30 @@ pub struct GetCommentQuery {
   pub lang: String,
   pub path: Option<String>,
   pub page_size: Option<i32>,
+  #[serde(default = "first_page")]
   pub page: i32,
+  /// Switches the reader view to cursor pagination, empty for the first page
+  pub cursor: Option<String>,
   pub sort_by: Option<String>,
   pub r#type: Option<String>,
+  /// Number of comments for `type=recent`
+  pub count: Option<u64>,
   pub owner: Option<String>,
   pub status: Option<String>,
   pub keyword: Option<String>,
+  // Admin list filters, `path` is taken by the reader view so the article is `url` here
+  pub url: Option<String>,
+  pub ip: Option<String>,
+  pub mail: Option<String>,
+  pub user_id: Option<i32>,
+  pub has_replies: Option<bool>,
+  // Millisecond timestamps
+  pub start: Option<i64>,
+  pub end: Option<i64>,
+}
+
+fn first_page() -> i32 {
+  1
 }
 
 impl GetCommentQuery {
@@ -219,6 +288,61 @@ pub fn has_forbidden_word(comment: &str, forbidden_words: &Vec<String>) -> bool
   false
 }
 
+/// Who is reading a comment page
+pub struct CommentViewer {
+  pub token: Result<String, AppError>,
+  /// Value of the `IDENTITY_COOKIE`
+  pub identity: Option<String>,
+  pub ip: String,
+  pub ua: String,
+}
+
+/// Which root comments of a thread a reader asks for
+#[derive(Debug, Clone)]
+pub enum CommentPosition {
+  Page(i32),
+  /// The `next` value of the previous page, empty for the first one
+  Cursor(String),
+}
+
+/// Decoded `next` cursor. Hot ranks change as comments age, so that sort pages by offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageCursor {
+  Key(RootCursor),
+  Offset(u64),
+}
+
+impl PageCursor {
+  pub fn encode(&self) -> String {
+    let raw = match self {
+      Self::Key(RootCursor { sticky, key, id }) => format!("k.{sticky}.{key}.{id}"),
+      Self::Offset(offset) => format!("o.{offset}"),
+    };
+    URL_SAFE_NO_PAD.encode(raw)
+  }
+
+  pub fn decode(cursor: &str) -> Option<Self> {
+    let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    match raw.split('.').collect::<Vec<_>>().as_slice() {
+      ["k", sticky, key, id] => Some(Self::Key(RootCursor {
+        sticky: sticky.parse().ok()?,
+        key: key.parse().ok()?,
+        id: id.parse().ok()?,
+      })),
+      ["o", offset] => Some(Self::Offset(offset.parse().ok()?)),
+      _ => None,
+    }
+  }
+}
+
+/// Who is asking to change or delete a comment
+pub enum CommentEditor {
+  /// Mail of a logged-in user, who must own the comment or be an administrator
+  User(String),
+  /// Edit token returned to an anonymous author when the comment was created
+  Author(String),
+}
+
 pub enum UserType {
   Anonymous,
   Guest(String),
@@ -230,6 +354,13 @@ pub struct CreateCommentQuery {
   pub lang: String,
 }
 
+#[derive(Deserialize)]
+pub struct PreviewCommentBody {
+  pub comment: String,
+  // path, whose commenters can be mentioned
+  pub url: String,
+}
+
 #[derive(Deserialize, Clone)]
 pub struct CreateCommentBody {
   pub comment: String,
@@ -251,6 +382,89 @@ pub struct CreateCommentBody {
   pub at: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct ReportCommentQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize)]
+pub struct ReportCommentBody {
+  pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LikeCommentQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize)]
+pub struct LikeCommentBody {
+  // Defaults to liking, `false` takes the like back
+  pub like: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ReactCommentQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize)]
+pub struct ReactCommentBody {
+  pub reaction: String,
+  // Defaults to reacting, `false` takes the reaction back
+  pub react: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct RestoreCommentQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentRepliesQuery {
+  pub lang: String,
+  #[serde(default = "first_page")]
+  pub page: i32,
+  pub page_size: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct CommentMentionsQuery {
+  pub lang: String,
+  pub path: String,
+}
+
+#[derive(Deserialize)]
+pub struct CommentRevisionsQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize)]
+pub struct BulkCommentQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize, Default)]
+pub struct BulkCommentFilter {
+  pub status: Option<String>,
+  pub keyword: Option<String>,
+  pub ip: Option<String>,
+  pub mail: Option<String>,
+  pub path: Option<String>,
+  // Millisecond timestamps, same unit as the `time` field of a comment
+  pub start: Option<i64>,
+  pub end: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkCommentBody {
+  // approve | spam | delete | restore
+  pub action: String,
+  pub ids: Option<Vec<u32>>,
+  pub filter: Option<BulkCommentFilter>,
+}
+
 #[derive(Deserialize)]
 pub struct UpdateCommentBody {
   pub status: Option<String>,
@@ -262,4 +476,5 @@ pub struct UpdateCommentBody {
   pub ua: Option<String>,
   pub url: Option<String>,
   pub sticky: Option<i8>,
+  pub locked: Option<bool>,
 }
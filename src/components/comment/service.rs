@@ -1,202 +1,816 @@
+use std::collections::{BTreeMap, HashMap};
+
 use actix_web::rt::spawn;
+use chrono::{DateTime, Duration};
 use helpers::{
-  jwt,
+  hash, jwt,
   time::{self, utc_now},
 };
 use instant_akismet::CheckResult;
-use sea_orm::{ItemsAndPagesNumber, Set};
+use sea_orm::{IntoActiveModel, ItemsAndPagesNumber, Order, Set};
 use serde_json::{Value, json};
+use similar::TextDiff;
 
 use crate::{
   app::AppState,
   components::comment::model::*,
-  entities::wl_comment,
+  entities::{wl_comment, wl_users},
   helpers::{
+    auth::verify_admin,
     avatar::get_avatar,
     email::{Notification, NotifyType, send_email_notification},
-    markdown::render_md_to_html,
+    markdown::{find_mentions, render_comment},
+    mention::Commenter,
+    path::normalize_path,
     spam::check_comment,
     ua,
   },
   prelude::AppError,
+  repository::{BulkAction, CommentAuthorBy, CommentFilter, ShadowViewer, VisitorBy},
   types::ServiceResult,
 };
 
+/// How a thread takes new comments, after its path settings
+struct ThreadPolicy {
+  closed: bool,
+  comment_audit: bool,
+}
+
+async fn get_thread_policy(state: &AppState, path: &str) -> ServiceResult<ThreadPolicy> {
+  let setting = state.repo.path_setting().get_setting(path).await?;
+  let mut closed = setting.as_ref().is_some_and(|setting| setting.closed);
+  if !closed
+    && let Some(days) = setting.as_ref().and_then(|setting| setting.auto_close_days)
+    && let Some(first) = state.repo.comment().get_first_comment_time(path).await?
+  {
+    closed = utc_now() - first > Duration::days(days as i64);
+  }
+  Ok(ThreadPolicy {
+    closed,
+    comment_audit: setting
+      .and_then(|setting| setting.comment_audit)
+      .unwrap_or(state.comment_audit),
+  })
+}
+
+/// A commenter is trusted when an administrator says so, or when they have enough
+/// approved comments and no spam within the configured window.
+async fn is_trusted_commenter(
+  state: &AppState,
+  author: CommentAuthorBy<'_>,
+) -> ServiceResult<bool> {
+  if matches!(author, CommentAuthorBy::Mail(mail) if mail.is_empty()) {
+    return Ok(false);
+  }
+  if let Some(trust) = state.repo.trust().get_trust(author.clone()).await? {
+    return Ok(trust.trusted);
+  }
+  if state.trusted_comment_count == 0 {
+    return Ok(false);
+  }
+  let approved = state
+    .repo
+    .comment()
+    .get_comment_count_by_author(author.clone(), "approved", None)
+    .await?;
+  if approved < state.trusted_comment_count {
+    return Ok(false);
+  }
+  let since = utc_now() - Duration::days(state.trusted_spam_window);
+  let spam = state
+    .repo
+    .comment()
+    .get_comment_count_by_author(author, "spam", Some(since))
+    .await?;
+  Ok(spam == 0)
+}
+
+/// Who is reading a thread, as far as comment visibility goes
+struct Reader {
+  is_admin: bool,
+  /// Any logged-in reader
+  user_id: Option<i32>,
+  /// A logged-in reader who is shadow banned and may see their own hidden comments
+  shadow_banned_id: Option<i32>,
+  /// Shadow-banned comments from the identity cookie of an anonymous commenter
+  shadow_banned_comments: Vec<u32>,
+}
+
+impl Reader {
+  fn viewer(&self) -> Option<ShadowViewer<'_>> {
+    match self.shadow_banned_id {
+      Some(user_id) => Some(ShadowViewer::UserId(user_id)),
+      None if !self.shadow_banned_comments.is_empty() => {
+        Some(ShadowViewer::Comments(&self.shadow_banned_comments))
+      }
+      None => None,
+    }
+  }
+}
+
+fn identity_key(state: &AppState) -> String {
+  format!("{}:identity", state.jwt_token)
+}
+
+fn verify_identity(state: &AppState, identity: &str) -> Option<Identity> {
+  jwt::verify::<Identity>(identity, &identity_key(state))
+    .ok()
+    .map(|t| t.claims.data)
+}
+
+/// The identity cookie of an anonymous commenter once they posted `comment_id`, keeping the
+/// comments of the cookie they already had
+pub fn sign_identity(state: &AppState, previous: Option<&str>, comment_id: u32) -> Option<String> {
+  let mut identity = previous
+    .and_then(|previous| verify_identity(state, previous))
+    .unwrap_or_default();
+  identity.comments.push(comment_id);
+  let skip = identity
+    .comments
+    .len()
+    .saturating_sub(IDENTITY_COMMENT_LIMIT);
+  identity.comments.drain(..skip);
+  jwt::sign(identity, &identity_key(state), 31536000).ok()
+}
+
+async fn identify_reader(
+  state: &AppState,
+  token: Result<String, AppError>,
+  identity: Option<String>,
+) -> ServiceResult<Reader> {
+  let mut reader = Reader {
+    is_admin: false,
+    user_id: None,
+    shadow_banned_id: None,
+    shadow_banned_comments: vec![],
+  };
+  // Only the comments that are still shadow banned matter, readers without any keep the cache
+  if let Some(identity) = identity.and_then(|identity| verify_identity(state, &identity))
+    && !identity.comments.is_empty()
+  {
+    reader.shadow_banned_comments = state
+      .repo
+      .comment()
+      .get_shadow_banned_ids(&identity.comments)
+      .await?;
+  }
+  if let Ok(token) = token {
+    if let Ok(email) = jwt::verify::<String>(&token, &state.jwt_token).map(|t| t.claims.data) {
+      if let Some(user) = state.repo.user().get_user_by_email(&email).await? {
+        reader.user_id = Some(user.id as i32);
+        if user.user_type == "administrator" {
+          reader.is_admin = true;
+        } else if state
+          .repo
+          .shadow_ban()
+          .is_shadow_banned(Some(user.id as i32), &user.email, "")
+          .await?
+        {
+          reader.shadow_banned_id = Some(user.id as i32);
+        }
+      }
+    }
+  }
+  Ok(reader)
+}
+
 pub async fn get_comment_info(
   state: &AppState,
   path: String,
-  page: i32,
+  position: CommentPosition,
   page_size: i32,
   sort_by: String,
-  token: Result<String, AppError>,
+  viewer: CommentViewer,
 ) -> ServiceResult<Value> {
-  if let Some(result) = state.comment_cache.lock().unwrap().get(path.clone(), page) {
-    return Ok(result);
-  }
+  let path = normalize_path(&path, state.path_ignore_case);
+  let CommentViewer {
+    token,
+    identity,
+    ip,
+    ua,
+  } = viewer;
+  let reader = identify_reader(state, token, identity).await?;
+  let is_admin = reader.is_admin;
+  let viewer = reader.viewer();
 
-  let mut is_admin = false;
-  if let Ok(token) = token {
-    if let Ok(email) = jwt::verify::<String>(&token, &state.jwt_token).map(|t| t.claims.data) {
-      if state
-        .repo
-        .user()
-        .is_admin_user(&email)
-        .await
-        .unwrap_or(false)
-      {
-        is_admin = true;
+  // Administrators and shadow-banned viewers see extra comments, so their view is never cached.
+  // Neither are cursor pages, which start wherever the reader stopped.
+  let cached_page = match position {
+    CommentPosition::Page(page) if !is_admin && viewer.is_none() => Some(page),
+    _ => None,
+  };
+  let cached = cached_page.and_then(|page| {
+    state
+      .comment_cache
+      .lock()
+      .unwrap()
+      .get(path.clone(), page, page_size, sort_by.clone())
+  });
+  let mut data = match cached {
+    Some(data) => data,
+    None => {
+      let data = build_comment_page(
+        state,
+        &path,
+        position,
+        page_size,
+        sort_by.clone(),
+        is_admin,
+        viewer,
+      )
+      .await?;
+      if let Some(page) = cached_page {
+        state.comment_cache.lock().unwrap().insert(
+          path.clone(),
+          page,
+          page_size,
+          sort_by,
+          data.clone(),
+        );
       }
+      data
     }
+  };
+  // Auto-closing depends on the time, so the flag is added after the cache
+  data["closed"] = json!(get_thread_policy(state, &path).await?.closed);
+  // The viewer's own reactions differ per request, so they are added after the cache
+  if !state.comment_reactions.is_empty() {
+    let fingerprint = hash::md5(&format!("{ip}{ua}"));
+    let visitor = match reader.user_id {
+      Some(user_id) => VisitorBy::UserId(user_id),
+      None => VisitorBy::Fingerprint(&fingerprint),
+    };
+    attach_visitor_reactions(state, &mut data, visitor).await?;
   }
-  let (
-    ItemsAndPagesNumber {
-      number_of_items,
-      number_of_pages,
-    },
-    parrent_comments,
-  ) = state
+  Ok(data)
+}
+
+/// Add `myReactions` to every entry of a comment page
+async fn attach_visitor_reactions(
+  state: &AppState,
+  data: &mut Value,
+  visitor: VisitorBy<'_>,
+) -> ServiceResult<()> {
+  let Some(entries) = data["data"].as_array_mut() else {
+    return Ok(());
+  };
+  let ids = entries
+    .iter()
+    .flat_map(|entry| {
+      std::iter::once(entry).chain(entry["children"].as_array().into_iter().flatten())
+    })
+    .filter_map(|entry| entry["objectId"].as_i64())
+    .map(|id| id as i32)
+    .collect();
+  let reactions = state
     .repo
-    .comment()
-    .get_root_comment(&path, sort_by, page as u64, page_size as u64, is_admin)
+    .reaction()
+    .get_visitor_reactions(ids, visitor)
     .await?;
+  let mine = |entry: &Value| {
+    let id = entry["objectId"].as_i64().unwrap_or_default() as i32;
+    json!(
+      reactions
+        .iter()
+        .filter(|(comment_id, _)| *comment_id == id)
+        .map(|(_, reaction)| reaction.clone())
+        .collect::<Vec<_>>()
+    )
+  };
+  for entry in entries.iter_mut() {
+    entry["myReactions"] = mine(entry);
+    if let Some(children) = entry["children"].as_array_mut() {
+      for child in children.iter_mut() {
+        child["myReactions"] = mine(child);
+      }
+    }
+  }
+  Ok(())
+}
+
+async fn build_comment_page(
+  state: &AppState,
+  path: &str,
+  position: CommentPosition,
+  page_size: i32,
+  sort_by: String,
+  is_admin: bool,
+  viewer: Option<ShadowViewer<'_>>,
+) -> ServiceResult<Value> {
+  let (number_of_items, number_of_pages, parrent_comments, next) = match &position {
+    CommentPosition::Page(page) => {
+      let (
+        ItemsAndPagesNumber {
+          number_of_items,
+          number_of_pages,
+        },
+        parrent_comments,
+      ) = if sort_by == "hot" {
+        let page_size = page_size.max(1) as u64;
+        let (number_of_items, comments) = get_hot_root_comments(
+          state,
+          path,
+          (page.max(&1) - 1) as u64 * page_size,
+          page_size,
+          is_admin,
+          viewer.clone(),
+        )
+        .await?;
+        (
+          ItemsAndPagesNumber {
+            number_of_items,
+            number_of_pages: number_of_items.div_ceil(page_size),
+          },
+          comments,
+        )
+      } else {
+        state
+          .repo
+          .comment()
+          .get_root_comment(
+            path,
+            sort_by.clone(),
+            *page as u64,
+            page_size as u64,
+            is_admin,
+            viewer.clone(),
+          )
+          .await?
+      };
+      (number_of_items, number_of_pages, parrent_comments, None)
+    }
+    CommentPosition::Cursor(cursor) => {
+      get_root_comments_by_cursor(
+        state,
+        path,
+        cursor,
+        page_size,
+        &sort_by,
+        is_admin,
+        viewer.clone(),
+      )
+      .await?
+    }
+  };
   // Get comment count for articles
   let mut count = number_of_items;
   let total_pages = number_of_pages;
-  let levels = state.levels.as_ref();
   let mut comment_data = vec![];
+  let commenters = get_thread_commenters(state, path).await?;
 
   for parrent_comment in parrent_comments {
-    let c = state
+    let mut parrent_data =
+      build_comment_entry(state, parrent_comment.clone(), is_admin, &commenters).await?;
+
+    let inline_limit = (state.inline_reply_limit > 0).then_some(state.inline_reply_limit);
+    let (reply_count, subcomments) = state
       .repo
       .comment()
-      .get_comment_count_by_nick_and_mail(
-        parrent_comment.nick.clone(),
-        parrent_comment.mail.clone(),
+      .get_subcomments(
+        path,
+        parrent_comment.id,
+        is_admin,
+        viewer.clone(),
+        0,
+        inline_limit,
       )
       .await?;
-    let level = levels.map(|levels| get_level(c as usize, levels));
-    let mut parrent_data = build_data_entry(
-      parrent_comment.clone(),
-      level,
-      &state.ip2region,
-      state.disable_useragent,
-      state.disable_region,
-    );
+    count += reply_count;
+    parrent_data.reply_count = Some(reply_count);
+    parrent_data.has_more = Some(reply_count > subcomments.len() as u64);
 
-    if let Some(user_id) = parrent_data.user_id {
-      if let Some(user) = state.repo.user().get_user_by_id(user_id as u32).await? {
-        parrent_data.label = user.label;
-        parrent_data.r#type = Some(user.user_type);
-        if let Some(avatar) = user.avatar {
-          parrent_data.avatar = avatar;
-        }
-      }
-    } else {
-      parrent_data.avatar =
-        get_avatar(&parrent_comment.mail.clone().unwrap_or("default".to_owned()));
+    for subcomment in subcomments {
+      let subcomment_data =
+        build_reply_entry(state, &parrent_comment, subcomment, is_admin, &commenters).await?;
+      parrent_data.children.push(subcomment_data)
     }
+    comment_data.push(parrent_data)
+  }
+  if !state.comment_reactions.is_empty() {
+    attach_reaction_counts(state, &mut comment_data).await?;
+  }
 
-    if is_admin {
-      parrent_data.mail = parrent_comment.mail.clone();
-      parrent_data.ip = parrent_comment.ip.clone();
-    }
+  let mut data = json!({
+    "count": count,
+    "data": comment_data,
+    "pageSize": page_size,
+    "totalPages": total_pages
+  });
+  match position {
+    CommentPosition::Page(page) => data["page"] = json!(page),
+    CommentPosition::Cursor(_) => data["next"] = json!(next),
+  }
+  Ok(data)
+}
 
-    let subcomments = state
+/// Root comments for cursor pagination: total count, number of pages, the comments and the
+/// cursor of the next page
+async fn get_root_comments_by_cursor(
+  state: &AppState,
+  path: &str,
+  cursor: &str,
+  page_size: i32,
+  sort_by: &str,
+  is_admin: bool,
+  viewer: Option<ShadowViewer<'_>>,
+) -> ServiceResult<(u64, u64, Vec<wl_comment::Model>, Option<String>)> {
+  let cursor = if cursor.is_empty() {
+    None
+  } else {
+    Some(PageCursor::decode(cursor).ok_or(AppError::Error)?)
+  };
+  let limit = page_size.max(1) as u64;
+  let (count, comments, next) = if sort_by == "hot" {
+    let offset = match cursor {
+      None => 0,
+      Some(PageCursor::Offset(offset)) => offset,
+      Some(PageCursor::Key(_)) => return Err(AppError::Error),
+    };
+    let (count, comments) =
+      get_hot_root_comments(state, path, offset, limit, is_admin, viewer).await?;
+    let end = offset + comments.len() as u64;
+    (
+      count,
+      comments,
+      (end < count).then_some(PageCursor::Offset(end)),
+    )
+  } else {
+    let cursor = match cursor {
+      None => None,
+      Some(PageCursor::Key(cursor)) => Some(cursor),
+      Some(PageCursor::Offset(_)) => return Err(AppError::Error),
+    };
+    let (count, comments, next) = state
       .repo
       .comment()
-      .get_subcomments(&path, parrent_comment.id, is_admin)
+      .get_root_comments_after(path, sort_by, cursor, limit, is_admin, viewer)
       .await?;
-    count += subcomments.len() as u64;
+    (count, comments, next.map(PageCursor::Key))
+  };
+  Ok((
+    count,
+    count.div_ceil(limit),
+    comments,
+    next.map(|next| next.encode()),
+  ))
+}
 
-    for subcomment in subcomments {
-      let c = state
-        .repo
-        .comment()
-        .get_comment_count_by_nick_and_mail(
-          parrent_comment.nick.clone(),
-          parrent_comment.mail.clone(),
-        )
-        .await?;
-      let level = levels.map(|levels| get_level(c as usize, levels));
-      let mut subcomment_data = build_data_entry(
-        subcomment.clone(),
-        level,
-        &state.ip2region,
-        state.disable_useragent,
-        state.disable_region,
-      );
+/// Latest approved comments across the site, for Waline's recent comments widget
+pub async fn get_recent_comments(state: &AppState, count: u64) -> ServiceResult<Value> {
+  let comments = state
+    .repo
+    .comment()
+    .get_recent_comments(count.clamp(1, 50))
+    .await?;
+  let mut data = vec![];
+  for comment in comments {
+    let commenters =
+      get_thread_commenters(state, comment.url.as_deref().unwrap_or_default()).await?;
+    data.push(build_comment_entry(state, comment, false, &commenters).await?);
+  }
+  Ok(json!(data))
+}
 
-      if let Some(user_id) = subcomment_data.user_id {
-        let user = state.repo.user().get_user_by_id(user_id as u32).await?;
-        if let Some(user) = user {
-          subcomment_data.label = user.label;
-          subcomment_data.r#type = Some(user.user_type);
-          if let Some(avatar) = user.avatar {
-            parrent_data.avatar = avatar;
-          }
-        }
-      } else {
-        parrent_data.avatar =
-          get_avatar(&parrent_comment.mail.clone().unwrap_or("default".to_owned()));
-      }
+/// Comment count of each path in the order they were asked for, a single path gets a bare number
+pub async fn get_path_comment_counts(state: &AppState, paths: Vec<String>) -> ServiceResult<Value> {
+  let paths: Vec<String> = paths
+    .iter()
+    .map(|path| normalize_path(path, state.path_ignore_case))
+    .collect();
+  let counts: HashMap<String, i64> = state
+    .repo
+    .comment()
+    .get_comment_counts_by_paths(paths.clone())
+    .await?
+    .into_iter()
+    .filter_map(|(path, count)| path.map(|path| (path, count)))
+    .collect();
+  let counts: Vec<i64> = paths
+    .iter()
+    .map(|path| counts.get(path).copied().unwrap_or(0))
+    .collect();
+  Ok(match counts.as_slice() {
+    [count] => json!(count),
+    _ => json!(counts),
+  })
+}
 
-      if is_admin {
-        subcomment_data.mail = subcomment_data.mail.clone();
-        subcomment_data.ip = subcomment_data.ip.clone();
+/// Entry of a comment with its author's level, label and avatar. Only administrators see
+/// mail and IP, and shadow-banned comments look approved to everyone else.
+async fn build_comment_entry(
+  state: &AppState,
+  comment: wl_comment::Model,
+  is_admin: bool,
+  commenters: &[Commenter],
+) -> ServiceResult<DataEntry> {
+  let c = state
+    .repo
+    .comment()
+    .get_comment_count_by_nick_and_mail(comment.nick.clone(), comment.mail.clone())
+    .await?;
+  let level = state
+    .levels
+    .as_ref()
+    .map(|levels| get_level(c as usize, levels));
+  let mut data = build_data_entry(
+    comment.clone(),
+    level,
+    &state.ip2region,
+    state.disable_useragent,
+    state.disable_region,
+    &state.markdown,
+    commenters,
+  );
+
+  if let Some(user_id) = data.user_id {
+    let user = state.repo.user().get_user_by_id(user_id as u32).await?;
+    if let Some(user) = user {
+      data.label = user.label;
+      data.r#type = Some(user.user_type);
+      if let Some(avatar) = user.avatar {
+        data.avatar = avatar;
       }
-      subcomment_data.reply_user = Some(json!({
-        "avatar": get_avatar(&parrent_comment.mail.clone().unwrap_or("default".to_owned())),
-        "link": parrent_comment.link,
-        "nick": parrent_comment.nick,
-      }));
-      parrent_data.children.push(subcomment_data)
     }
-    comment_data.push(parrent_data)
+  } else {
+    data.avatar = get_avatar(&comment.mail.clone().unwrap_or("default".to_owned()));
   }
 
-  let data = json!({
+  if is_admin {
+    data.mail = comment.mail;
+    data.ip = comment.ip;
+  } else if data.status == "shadowban" {
+    data.status = "approved".to_string();
+  }
+  Ok(data)
+}
+
+/// Entry of a reply shown under `parent`
+async fn build_reply_entry(
+  state: &AppState,
+  parent: &wl_comment::Model,
+  subcomment: wl_comment::Model,
+  is_admin: bool,
+  commenters: &[Commenter],
+) -> ServiceResult<DataEntry> {
+  let mut subcomment_data = build_comment_entry(state, subcomment, is_admin, commenters).await?;
+  subcomment_data.reply_user = Some(json!({
+    "avatar": get_avatar(&parent.mail.clone().unwrap_or("default".to_owned())),
+    "link": parent.link,
+    "nick": parent.nick,
+  }));
+  Ok(subcomment_data)
+}
+
+/// One page of the replies to a root comment, for threads with more than `INLINE_REPLY_LIMIT`
+pub async fn get_comment_replies(
+  state: &AppState,
+  id: u32,
+  page: i32,
+  page_size: i32,
+  viewer: CommentViewer,
+) -> ServiceResult<Value> {
+  let CommentViewer {
+    token,
+    identity,
+    ip,
+    ua,
+  } = viewer;
+  let reader = identify_reader(state, token, identity).await?;
+  let root = state
+    .repo
+    .comment()
+    .get_visible_comment(id, reader.is_admin, reader.viewer())
+    .await?
+    .ok_or(AppError::Error)?;
+  let page = page.max(1) as u64;
+  let page_size = page_size.clamp(1, 100) as u64;
+  let (count, subcomments) = state
+    .repo
+    .comment()
+    .get_subcomments(
+      root.url.as_deref().unwrap_or_default(),
+      id,
+      reader.is_admin,
+      reader.viewer(),
+      (page - 1) * page_size,
+      Some(page_size),
+    )
+    .await?;
+  let commenters = get_thread_commenters(state, root.url.as_deref().unwrap_or_default()).await?;
+  let mut replies = vec![];
+  for subcomment in subcomments {
+    replies.push(build_reply_entry(state, &root, subcomment, reader.is_admin, &commenters).await?);
+  }
+  if !state.comment_reactions.is_empty() {
+    attach_reaction_counts(state, &mut replies).await?;
+  }
+  let mut data = json!({
     "count": count,
-    "data": comment_data,
+    "data": replies,
     "page": page,
     "pageSize": page_size,
-    "totalPages": total_pages
+    "totalPages": count.div_ceil(page_size)
   });
-  state
-    .comment_cache
-    .lock()
-    .unwrap()
-    .insert(path, page, data.clone());
+  if !state.comment_reactions.is_empty() {
+    let fingerprint = hash::md5(&format!("{ip}{ua}"));
+    let visitor = match reader.user_id {
+      Some(user_id) => VisitorBy::UserId(user_id),
+      None => VisitorBy::Fingerprint(&fingerprint),
+    };
+    attach_visitor_reactions(state, &mut data, visitor).await?;
+  }
   Ok(data)
 }
 
+/// Ids of every root comment ranked by likes and replies, discounted by their age in hours with
+/// `HOT_DECAY`. Sticky comments still come first. The ranking is cached for a minute, except for
+/// shadow-banned viewers who also see their own comments.
+async fn get_hot_root_ids(
+  state: &AppState,
+  path: &str,
+  is_admin: bool,
+  viewer: Option<ShadowViewer<'_>>,
+) -> ServiceResult<Vec<u32>> {
+  let cacheable = viewer.is_none();
+  if cacheable && let Some(ids) = state.comment_cache.lock().unwrap().get_hot(path, is_admin) {
+    return Ok(ids);
+  }
+  let stats = state
+    .repo
+    .comment()
+    .get_root_comment_stats(path, is_admin, viewer)
+    .await?;
+  let reply_counts: HashMap<i32, i64> = state
+    .repo
+    .comment()
+    .get_reply_counts(stats.iter().map(|(id, ..)| *id as i32).collect())
+    .await?
+    .into_iter()
+    .collect();
+  let now = utc_now();
+  let mut ranked: Vec<(i8, f64, u32)> = stats
+    .into_iter()
+    .map(|(id, like, sticky, inserted_at)| {
+      let likes = like.unwrap_or(0).max(0) as f64;
+      let replies = reply_counts.get(&(id as i32)).copied().unwrap_or(0) as f64;
+      let age = inserted_at
+        .map(|inserted_at| (now - inserted_at).num_minutes().max(0) as f64 / 60.0)
+        .unwrap_or(0.0);
+      let score = (likes + 2.0 * replies + 1.0) / (age + 2.0).powf(state.hot_decay);
+      (sticky.unwrap_or(0), score, id)
+    })
+    .collect();
+  ranked.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)));
+  let ids: Vec<u32> = ranked.into_iter().map(|(_, _, id)| id).collect();
+  if cacheable {
+    state
+      .comment_cache
+      .lock()
+      .unwrap()
+      .insert_hot(path, is_admin, ids.clone());
+  }
+  Ok(ids)
+}
+
+/// The number of root comments of a path and `limit` of them from `offset` on, hottest first
+async fn get_hot_root_comments(
+  state: &AppState,
+  path: &str,
+  offset: u64,
+  limit: u64,
+  is_admin: bool,
+  viewer: Option<ShadowViewer<'_>>,
+) -> ServiceResult<(u64, Vec<wl_comment::Model>)> {
+  let ranked = get_hot_root_ids(state, path, is_admin, viewer).await?;
+  let ids: Vec<u32> = ranked
+    .iter()
+    .skip(offset as usize)
+    .take(limit as usize)
+    .copied()
+    .collect();
+  let mut comments = state
+    .repo
+    .comment()
+    .get_comments_by_ids(ids.clone())
+    .await?;
+  comments.sort_by_key(|comment| ids.iter().position(|id| *id == comment.id));
+  Ok((ranked.len() as u64, comments))
+}
+
+/// Fill in how often each configured reaction was given to the comments and their replies
+async fn attach_reaction_counts(state: &AppState, entries: &mut [DataEntry]) -> ServiceResult<()> {
+  let ids = entries
+    .iter()
+    .flat_map(|entry| std::iter::once(entry).chain(entry.children.iter()))
+    .map(|entry| entry.object_id as i32)
+    .collect();
+  let counts = state.repo.reaction().get_reaction_counts(ids).await?;
+  let reactions_of = |id: u32| {
+    let mut reactions: BTreeMap<String, i64> = state
+      .comment_reactions
+      .iter()
+      .map(|reaction| (reaction.clone(), 0))
+      .collect();
+    for (comment_id, reaction, count) in &counts {
+      if *comment_id == id as i32
+        && let Some(total) = reactions.get_mut(reaction)
+      {
+        *total = *count;
+      }
+    }
+    reactions
+  };
+  for entry in entries.iter_mut() {
+    entry.reactions = Some(reactions_of(entry.object_id));
+    for child in entry.children.iter_mut() {
+      child.reactions = Some(reactions_of(child.object_id));
+    }
+  }
+  Ok(())
+}
+
 pub async fn get_comment_info_by_admin(
   state: &AppState,
-  owner: String,
   email: String,
-  keyword: String,
-  status: String,
-  page: i32,
+  query: GetCommentQuery,
 ) -> ServiceResult<Value> {
+  let GetCommentQuery {
+    page_size,
+    page,
+    sort_by,
+    owner,
+    status,
+    keyword,
+    url,
+    ip,
+    mail,
+    user_id,
+    has_replies,
+    start,
+    end,
+    ..
+  } = query;
+  let owner = owner.unwrap_or_default();
+  let status = status.unwrap_or_default();
+  let page_size = page_size.unwrap_or(10).clamp(1, 100) as u64;
+  let (sort_col, sort_ord) = match sort_by.as_deref() {
+    Some("insertedAt_asc") => (wl_comment::Column::InsertedAt, Order::Asc),
+    Some("like_desc") => (wl_comment::Column::Like, Order::Desc),
+    Some("like_asc") => (wl_comment::Column::Like, Order::Asc),
+    Some("url_asc") => (wl_comment::Column::Url, Order::Asc),
+    Some("url_desc") => (wl_comment::Column::Url, Order::Desc),
+    _ => (wl_comment::Column::InsertedAt, Order::Desc),
+  };
+  let filter = CommentFilter {
+    status: Some(status.clone()),
+    keyword: keyword.filter(|keyword| !keyword.is_empty()),
+    ip,
+    // Own comments are matched by the mail of the administrator
+    mail: if owner == "mine" {
+      Some(email.clone())
+    } else {
+      mail
+    },
+    user_id,
+    path: url.map(|url| normalize_path(&url, state.path_ignore_case)),
+    start: start.and_then(DateTime::from_timestamp_millis),
+    end: end.and_then(DateTime::from_timestamp_millis),
+    has_replies,
+    ..Default::default()
+  };
   let (
     ItemsAndPagesNumber {
-      number_of_items: _,
+      number_of_items,
       number_of_pages,
     },
     comments,
   ) = state
     .repo
     .comment()
-    .get_comments_list_by_admin(&email, &status, &keyword, page as u64, 10, owner)
+    .get_comments_list_by_admin(&filter, sort_col, sort_ord, page as u64, page_size)
     .await?;
   let mut data = vec![];
+  let reports = if status == "reported" {
+    state
+      .repo
+      .report()
+      .get_reports_by_comment_ids(comments.iter().map(|c| c.id as i32).collect())
+      .await?
+  } else {
+    vec![]
+  };
 
   for comment in comments.iter() {
+    let commenters =
+      get_thread_commenters(state, comment.url.as_deref().unwrap_or_default()).await?;
     let mut data_entry = build_data_entry(
       comment.clone(),
       None,
       &state.ip2region,
       state.disable_useragent,
       state.disable_region,
+      &state.markdown,
+      &commenters,
     );
     if let Some(user_id) = data_entry.user_id {
       if let Some(user) = state.repo.user().get_user_by_id(user_id as u32).await? {
@@ -204,15 +818,36 @@ pub async fn get_comment_info_by_admin(
         data_entry.r#type = Some(user.user_type);
       }
     }
+    if status == "reported" {
+      data_entry.reports = Some(
+        reports
+          .iter()
+          .filter(|report| report.comment_id == comment.id as i32)
+          .filter_map(|report| report.reason.clone())
+          .collect(),
+      );
+    }
     data.push(data_entry);
   }
+  let mail = (owner == "mine").then_some(email.as_str());
+  let spam_count = state
+    .repo
+    .comment()
+    .get_comment_count_by_status("spam", mail)
+    .await?;
+  let waiting_count = state
+    .repo
+    .comment()
+    .get_comment_count_by_status("waiting", mail)
+    .await?;
   Ok(json!({
     "data": data,
     "page": page,
-    "pageSize": 10,
-    "spamCount": 0,
+    "pageSize": page_size,
+    "count": number_of_items,
+    "spamCount": spam_count,
     "totalPages": number_of_pages,
-    "waitingCount": 0,
+    "waitingCount": waiting_count,
   }))
 }
 
@@ -226,13 +861,43 @@ pub async fn create_comment<'a>(
   url: String,
   pid: Option<i32>,
   rid: Option<i32>,
-  _at: Option<String>,
+  at: Option<String>,
   ip: String,
   user_type: UserType,
   lang: String,
 ) -> Result<Value, AppError> {
+  let url = normalize_path(&url, state.path_ignore_case);
+  let policy = get_thread_policy(state, &url).await?;
+  // Closed threads only take comments from administrators
+  if policy.closed && !matches!(user_type, UserType::Administrator(_)) {
+    return Err(AppError::Forbidden);
+  }
+  // So are locked sub-threads
+  if !matches!(user_type, UserType::Administrator(_))
+    && state.repo.comment().is_reply_locked(pid, rid).await?
+  {
+    return Err(AppError::Forbidden);
+  }
   state.comment_cache.lock().unwrap().invalidate(&url);
-  let html_output = render_md_to_html(&comment);
+  let commenters = get_thread_commenters(state, &url).await?;
+  let mut mentioned = find_mentions(&comment, &state.markdown, &commenters);
+  // `at` names the commenter being replied to, whom the client links by itself
+  if let Some(at) = at
+    && let Some(commenter) = commenters.iter().find(|commenter| commenter.nick == at)
+    && !mentioned
+      .iter()
+      .any(|known| known.comment_id == commenter.comment_id)
+  {
+    mentioned.push(commenter);
+  }
+  let mut mentioned_mails: Vec<String> = mentioned
+    .iter()
+    .filter_map(|commenter| commenter.mail.clone())
+    .filter(|mentioned_mail| !mentioned_mail.is_empty() && *mentioned_mail != mail)
+    .collect();
+  mentioned_mails.sort();
+  mentioned_mails.dedup();
+  let html_output = render_comment(&comment, &state.markdown, &commenters);
   let mut avatar = get_avatar("");
   let mut new_comment = create_comment_model(
     None,
@@ -253,25 +918,27 @@ pub async fn create_comment<'a>(
     "os": os,
     "comment": html_output,
   });
+  let user_id = match &user_type {
+    UserType::Anonymous => None,
+    UserType::Guest(email) | UserType::Administrator(email) => state
+      .repo
+      .user()
+      .get_user_by_email(email)
+      .await?
+      .map(|user| user.id as i32),
+  };
+  let shadow_banned = !matches!(user_type, UserType::Administrator(_))
+    && state
+      .repo
+      .shadow_ban()
+      .is_shadow_banned(user_id, &mail, &ip)
+      .await?;
   match user_type {
     UserType::Anonymous => {
-      new_comment.status = Set(if state.comment_audit {
-        "waiting".to_string()
-      } else if has_forbidden_word(&comment, &state.forbidden_words) {
-        "spam".to_string()
-      } else if matches!(
-        check_comment(nick, mail, ip, comment).await?,
-        CheckResult::Ham
-      ) {
-        "approved".to_string()
-      } else {
-        "spam".to_string()
-      });
-    }
-    UserType::Guest(email) => {
-      if let Some(user) = state.repo.user().get_user_by_email(&email).await? {
-        new_comment.user_id = Set(Some(user.id as i32));
-        new_comment.status = Set(if state.comment_audit {
+      new_comment.status = Set(
+        if policy.comment_audit
+          && !is_trusted_commenter(state, CommentAuthorBy::Mail(&mail)).await?
+        {
           "waiting".to_string()
         } else if has_forbidden_word(&comment, &state.forbidden_words) {
           "spam".to_string()
@@ -282,7 +949,28 @@ pub async fn create_comment<'a>(
           "approved".to_string()
         } else {
           "spam".to_string()
-        });
+        },
+      );
+    }
+    UserType::Guest(email) => {
+      if let Some(user) = state.repo.user().get_user_by_email(&email).await? {
+        new_comment.user_id = Set(Some(user.id as i32));
+        new_comment.status = Set(
+          if policy.comment_audit
+            && !is_trusted_commenter(state, CommentAuthorBy::UserId(user.id as i32)).await?
+          {
+            "waiting".to_string()
+          } else if has_forbidden_word(&comment, &state.forbidden_words) {
+            "spam".to_string()
+          } else if matches!(
+            check_comment(nick, mail, ip, comment).await?,
+            CheckResult::Ham
+          ) {
+            "approved".to_string()
+          } else {
+            "spam".to_string()
+          },
+        );
         data["label"] = json!(user.label);
         data["mail"] = json!(user.email);
         data["type"] = json!(user.user_type);
@@ -310,6 +998,9 @@ pub async fn create_comment<'a>(
       }
     }
   }
+  if shadow_banned {
+    new_comment.status = Set("shadowban".to_string());
+  }
   let comment = state.repo.comment().create_comment(new_comment).await?;
   data["avatar"] = json!(avatar);
   data["like"] = json!(comment.like);
@@ -327,7 +1018,35 @@ pub async fn create_comment<'a>(
   if let Some(rid) = rid {
     data["rid"] = json!(rid);
   };
+  if comment.user_id.is_none()
+    && state.edit_grace_period > 0
+    && let Ok(edit_token) = jwt::sign(
+      comment.id.to_string(),
+      &edit_token_key(state),
+      state.edit_grace_period,
+    )
+  {
+    data["editToken"] = json!(edit_token);
+  }
+  if shadow_banned {
+    return Ok(data);
+  }
+  // Comments held for review or caught as spam do not notify the people they mention
+  if comment.status != "approved" {
+    mentioned_mails.clear();
+  }
   spawn(async move {
+    for recipient in mentioned_mails {
+      send_email_notification(Notification {
+        sender_name: comment.nick.clone().unwrap_or_default(),
+        sender_email: comment.mail.clone().unwrap_or_default(),
+        comment_id: comment.id,
+        comment: comment.comment.clone().unwrap_or_default(),
+        url: comment.url.clone().unwrap_or_default(),
+        notify_type: NotifyType::Mention(recipient),
+        lang: Some(&lang),
+      });
+    }
     send_email_notification(Notification {
       sender_name: comment.nick.unwrap(),
       sender_email: comment.mail.unwrap(),
@@ -341,33 +1060,102 @@ pub async fn create_comment<'a>(
   Ok(data)
 }
 
-pub async fn delete_comment(state: &AppState, id: u32, token: String) -> ServiceResult<()> {
-  let email = jwt::verify::<String>(&token, &state.jwt_token)?.claims.data;
-  let user = state
-    .repo
-    .user()
-    .get_user_by_email(&email)
+/// Everyone who commented in a thread, with their latest comment
+async fn get_thread_commenters(state: &AppState, path: &str) -> ServiceResult<Vec<Commenter>> {
+  let mut commenters: Vec<Commenter> = vec![];
+  for (comment_id, nick, mail) in state.repo.comment().get_thread_commenters(path).await? {
+    let Some(nick) = nick.filter(|nick| !nick.is_empty()) else {
+      continue;
+    };
+    if !commenters.iter().any(|commenter| commenter.nick == nick) {
+      commenters.push(Commenter {
+        nick,
+        mail,
+        comment_id,
+      });
+    }
+  }
+  Ok(commenters)
+}
+
+/// Html of a comment as it would be posted to a thread, without saving it
+pub async fn preview_comment(
+  state: &AppState,
+  comment: String,
+  url: String,
+) -> ServiceResult<Value> {
+  let url = normalize_path(&url, state.path_ignore_case);
+  let commenters = get_thread_commenters(state, &url).await?;
+  Ok(json!({ "html": render_comment(&comment, &state.markdown, &commenters) }))
+}
+
+/// Nicknames that can be mentioned in a thread, for autocomplete
+pub async fn get_thread_mentions(state: &AppState, path: String) -> ServiceResult<Value> {
+  let path = normalize_path(&path, state.path_ignore_case);
+  let nicks: Vec<String> = get_thread_commenters(state, &path)
     .await?
-    .ok_or(AppError::UserNotFound)?;
-  let pass = if user.user_type == "administrator" {
-    true
-  } else {
-    state.repo.comment().is_comment_owner(id, user.id).await?
-  };
-  if !pass {
-    return Err(AppError::Forbidden);
+    .into_iter()
+    .map(|commenter| commenter.nick)
+    .collect();
+  Ok(json!(nicks))
+}
+
+fn edit_token_key(state: &AppState) -> String {
+  format!("{}:edit", state.jwt_token)
+}
+
+/// Check that the editor may change the comment, and return the user behind a login token
+async fn authorize_editor(
+  state: &AppState,
+  id: u32,
+  editor: CommentEditor,
+) -> ServiceResult<Option<wl_users::Model>> {
+  match editor {
+    CommentEditor::User(email) => {
+      let user = state
+        .repo
+        .user()
+        .get_user_by_email(&email)
+        .await?
+        .ok_or(AppError::UserNotFound)?;
+      if user.user_type != "administrator"
+        && !state.repo.comment().is_comment_owner(id, user.id).await?
+      {
+        return Err(AppError::Forbidden);
+      }
+      Ok(Some(user))
+    }
+    CommentEditor::Author(token) => {
+      let comment_id = jwt::verify::<String>(&token, &edit_token_key(state))?
+        .claims
+        .data;
+      if comment_id != id.to_string() {
+        return Err(AppError::Forbidden);
+      }
+      Ok(None)
+    }
   }
-  state.repo.comment().delete_comment(id).await?;
+}
+
+pub async fn delete_comment(state: &AppState, id: u32, editor: CommentEditor) -> ServiceResult<()> {
+  authorize_editor(state, id, editor).await?;
+  state.repo.comment().trash_comment(id).await?;
   state.comment_cache.lock().unwrap().clear();
   Ok(())
 }
 
+pub async fn restore_comment(state: &AppState, id: u32, token: String) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let count = state.repo.comment().restore_comment(id).await?;
+  state.comment_cache.lock().unwrap().clear();
+  Ok(json!({ "count": count }))
+}
+
 pub async fn update_comment(
   state: &AppState,
-  email: String,
+  editor: CommentEditor,
   id: u32,
   status: Option<String>,
-  like: Option<bool>,
   comment: Option<String>,
   link: Option<String>,
   mail: Option<String>,
@@ -375,6 +1163,7 @@ pub async fn update_comment(
   ua: Option<String>,
   url: Option<String>,
   sticky: Option<i8>,
+  locked: Option<bool>,
 ) -> ServiceResult<Value> {
   let mut active_comment = wl_comment::ActiveModel {
     id: Set(id),
@@ -382,29 +1171,42 @@ pub async fn update_comment(
     ..Default::default()
   };
 
-  let user = state
-    .repo
-    .user()
-    .get_user_by_email(&email)
-    .await?
-    .ok_or(AppError::UserNotFound)?;
-
-  if !state.repo.comment().is_comment_owner(id, user.id).await? && user.user_type != "administrator"
-  {
-    return Err(AppError::Forbidden);
+  // Edit tokens only cover the content written by the author, not who they said they are
+  if let CommentEditor::Author(_) = editor {
+    if url.is_some() {
+      return Err(AppError::Forbidden);
+    }
+    if mail.is_some() || nick.is_some() {
+      let previous = state
+        .repo
+        .comment()
+        .get_comment(id)
+        .await?
+        .ok_or(AppError::Error)?;
+      if mail
+        .as_ref()
+        .is_some_and(|mail| previous.mail.as_ref() != Some(mail))
+        || nick
+          .as_ref()
+          .is_some_and(|nick| previous.nick.as_ref() != Some(nick))
+      {
+        return Err(AppError::Forbidden);
+      }
+    }
   }
-
-  if let Some(like) = like {
-    let comment = state
-      .repo
-      .comment()
-      .get_comment(id)
-      .await?
-      .ok_or(AppError::Error)?;
-    active_comment.like = Set(Some(comment.like.unwrap_or(0) + if like { 1 } else { -1 }));
+  let user = authorize_editor(state, id, editor).await?;
+  let is_admin = user
+    .as_ref()
+    .is_some_and(|user| user.user_type == "administrator");
+  // Moderation stays with administrators, so authors cannot publish what was held back
+  if !is_admin && (status.is_some() || sticky.is_some() || locked.is_some()) {
+    return Err(AppError::Forbidden);
   }
 
   if let Some(status) = status {
+    if status == "approved" {
+      state.repo.report().delete_reports(id).await?;
+    }
     active_comment.status = Set(status);
   }
 
@@ -412,7 +1214,67 @@ pub async fn update_comment(
     active_comment.sticky = Set(Some(sticky));
   }
 
+  if let Some(locked) = locked {
+    active_comment.locked = Set(locked);
+  }
+
   if let Some(comment) = comment {
+    let previous = state
+      .repo
+      .comment()
+      .get_comment(id)
+      .await?
+      .ok_or(AppError::Error)?;
+    if previous.comment.as_deref() != Some(comment.as_str()) {
+      let editor_mail = match &user {
+        Some(user) => Some(user.email.clone()),
+        None => Some(previous.mail.clone().unwrap_or_default()),
+      };
+      // An approved comment goes back to the moderators when its thread is audited, as if
+      // it were new, unless its author is trusted
+      let audited = !is_admin
+        && previous.status == "approved"
+        && get_thread_policy(state, previous.url.as_deref().unwrap_or_default())
+          .await?
+          .comment_audit
+        && !is_trusted_commenter(
+          state,
+          match &user {
+            Some(user) => CommentAuthorBy::UserId(user.id as i32),
+            None => CommentAuthorBy::Mail(previous.mail.as_deref().unwrap_or_default()),
+          },
+        )
+        .await?;
+      // Edits go through the same spam checks as new comments
+      if !is_admin
+        && (has_forbidden_word(&comment, &state.forbidden_words)
+          || !matches!(
+            check_comment(
+              nick.clone().or(previous.nick).unwrap_or_default(),
+              mail.clone().or(previous.mail).unwrap_or_default(),
+              previous.ip.unwrap_or_default(),
+              comment.clone(),
+            )
+            .await?,
+            CheckResult::Ham
+          ))
+      {
+        active_comment.status = Set("spam".to_string());
+      } else if audited {
+        active_comment.status = Set("waiting".to_string());
+      }
+      state
+        .repo
+        .revision()
+        .create_revision(
+          id,
+          previous.comment,
+          user.as_ref().map(|user| user.id as i32),
+          editor_mail,
+        )
+        .await?;
+      active_comment.edited_at = Set(Some(time::utc_now()));
+    }
     active_comment.comment = Set(Some(comment));
   }
 
@@ -433,7 +1295,7 @@ pub async fn update_comment(
   }
 
   if let Some(url) = url {
-    active_comment.url = Set(Some(url));
+    active_comment.url = Set(Some(normalize_path(&url, state.path_ignore_case)));
   }
 
   let updated_comment = state.repo.comment().update_comment(active_comment).await?;
@@ -443,7 +1305,16 @@ pub async fn update_comment(
   let time = updated_comment.created_at.unwrap().timestamp_millis();
   let pid = updated_comment.pid;
   let rid = updated_comment.rid;
-  let html_output = render_md_to_html(updated_comment.comment.clone().unwrap().as_str());
+  let edited_at = updated_comment
+    .edited_at
+    .map(|edited_at| edited_at.timestamp_millis());
+  let commenters =
+    get_thread_commenters(state, updated_comment.url.as_deref().unwrap_or_default()).await?;
+  let html_output = render_comment(
+    updated_comment.comment.clone().unwrap().as_str(),
+    &state.markdown,
+    &commenters,
+  );
   if state.repo.comment().is_anonymous(id).await? {
     let data = json!({
       "addr":"",
@@ -462,6 +1333,9 @@ pub async fn update_comment(
       "status": updated_comment.status,
       "time": time,
       "url": updated_comment.url,
+      "edited": edited_at.is_some(),
+      "editedAt": edited_at,
+      "locked": updated_comment.locked,
     });
     Ok(data)
   } else {
@@ -490,6 +1364,9 @@ pub async fn update_comment(
       "status": updated_comment.status,
       "time": time,
       "url": updated_comment.url,
+      "edited": edited_at.is_some(),
+      "editedAt": edited_at,
+      "locked": updated_comment.locked,
     });
     if let Some(pid) = pid {
       data["pid"] = json!(pid);
@@ -500,3 +1377,285 @@ pub async fn update_comment(
     Ok(data)
   }
 }
+
+pub async fn report_comment(
+  state: &AppState,
+  id: u32,
+  reason: Option<String>,
+  token: Option<String>,
+  ip: String,
+  lang: String,
+) -> ServiceResult<Value> {
+  let comment = state
+    .repo
+    .comment()
+    .get_comment(id)
+    .await?
+    .ok_or(AppError::Error)?;
+  let user_id = match token {
+    Some(token) => {
+      let email = jwt::verify::<String>(&token, &state.jwt_token)?.claims.data;
+      state
+        .repo
+        .user()
+        .get_user_by_email(&email)
+        .await?
+        .map(|user| user.id as i32)
+    }
+    None => None,
+  };
+  if !state.repo.report().has_reported(id, user_id, &ip).await? {
+    state
+      .repo
+      .report()
+      .create_report(id, user_id, ip, reason)
+      .await?;
+  }
+  let count = state.repo.report().get_report_count(id).await?;
+  if state.report_threshold > 0 && count >= state.report_threshold && comment.status == "approved" {
+    let mut active_comment = comment.clone().into_active_model();
+    active_comment.status = Set("waiting".to_string());
+    active_comment.updated_at = Set(Some(utc_now()));
+    state.repo.comment().update_comment(active_comment).await?;
+    state
+      .comment_cache
+      .lock()
+      .unwrap()
+      .invalidate(&comment.url.clone().unwrap_or_default());
+    spawn(async move {
+      send_email_notification(Notification {
+        sender_name: comment.nick.unwrap_or_default(),
+        sender_email: comment.mail.unwrap_or_default(),
+        comment_id: comment.id,
+        comment: comment.comment.unwrap_or_default(),
+        url: comment.url.unwrap_or_default(),
+        notify_type: NotifyType::ReportComment,
+        lang: Some(&lang),
+      });
+    });
+  }
+  Ok(json!({}))
+}
+
+pub async fn bulk_comment(
+  state: &AppState,
+  token: String,
+  body: BulkCommentBody,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let BulkCommentBody {
+    action: action_name,
+    ids,
+    filter,
+  } = body;
+  let action = match action_name.as_str() {
+    "approve" => BulkAction::Approve,
+    "spam" => BulkAction::Spam,
+    "delete" => BulkAction::Delete,
+    "restore" => BulkAction::Restore,
+    _ => return Err(AppError::Error),
+  };
+  let BulkCommentFilter {
+    status,
+    keyword,
+    ip,
+    mail,
+    path,
+    start,
+    end,
+  } = filter.unwrap_or_default();
+  let filter = CommentFilter {
+    ids,
+    status,
+    keyword,
+    ip,
+    mail,
+    path: path.map(|path| normalize_path(&path, state.path_ignore_case)),
+    start: start.and_then(DateTime::from_timestamp_millis),
+    end: end.and_then(DateTime::from_timestamp_millis),
+    ..Default::default()
+  };
+  // Refuse to touch every comment in the database by accident
+  if filter.is_empty() {
+    return Err(AppError::Error);
+  }
+  let count = state.repo.comment().bulk_moderate(&filter, action).await?;
+  if count > 0 {
+    state.comment_cache.lock().unwrap().clear();
+  }
+  Ok(json!({
+    "action": action_name,
+    "count": count,
+  }))
+}
+
+pub async fn get_comment_revisions(
+  state: &AppState,
+  id: u32,
+  token: String,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let current = state
+    .repo
+    .comment()
+    .get_comment(id)
+    .await?
+    .ok_or(AppError::Error)?;
+  let revisions = state.repo.revision().get_revisions(id).await?;
+  // Every revision is compared with the body that replaced it
+  let mut next_bodies: Vec<String> = revisions
+    .iter()
+    .skip(1)
+    .map(|revision| revision.comment.clone().unwrap_or_default())
+    .collect();
+  next_bodies.push(current.comment.unwrap_or_default());
+  let mut data = vec![];
+  for (revision, next) in revisions.into_iter().zip(next_bodies) {
+    let editor = match revision.user_id {
+      Some(user_id) => state.repo.user().get_user_by_id(user_id as u32).await?,
+      None => None,
+    };
+    let previous = revision.comment.unwrap_or_default();
+    let diff = TextDiff::from_lines(&previous, &next)
+      .unified_diff()
+      .header("previous", "next")
+      .to_string();
+    data.push(json!({
+      "id": revision.id,
+      "comment": previous,
+      "diff": diff,
+      "user_id": revision.user_id,
+      "nick": editor.as_ref().map(|user| user.display_name.clone()),
+      "mail": revision.mail.or(editor.map(|user| user.email)),
+      "time": revision.created_at.map(|time| time.timestamp_millis()),
+    }));
+  }
+  Ok(json!(data))
+}
+
+/// Return the user behind the token, and the fingerprint used when there is none
+async fn resolve_visitor(
+  state: &AppState,
+  token: Option<String>,
+  ip: &str,
+  ua: &str,
+) -> ServiceResult<(Option<i32>, String)> {
+  let user_id = match token {
+    Some(token) => {
+      let email = jwt::verify::<String>(&token, &state.jwt_token)?.claims.data;
+      state
+        .repo
+        .user()
+        .get_user_by_email(&email)
+        .await?
+        .map(|user| user.id as i32)
+    }
+    None => None,
+  };
+  Ok((user_id, hash::md5(&format!("{ip}{ua}"))))
+}
+
+pub async fn like_comment(
+  state: &AppState,
+  id: u32,
+  like: bool,
+  token: Option<String>,
+  ip: String,
+  ua: String,
+) -> ServiceResult<Value> {
+  let comment = state
+    .repo
+    .comment()
+    .get_comment(id)
+    .await?
+    .filter(|comment| comment.deleted_at.is_none())
+    .ok_or(AppError::Error)?;
+  let (user_id, fingerprint) = resolve_visitor(state, token, &ip, &ua).await?;
+  let liker = match user_id {
+    Some(user_id) => VisitorBy::UserId(user_id),
+    None => VisitorBy::Fingerprint(&fingerprint),
+  };
+  if state.repo.like().set_like(id, liker, like).await? {
+    state
+      .comment_cache
+      .lock()
+      .unwrap()
+      .invalidate(&comment.url.unwrap_or_default());
+  }
+  let like_count = state
+    .repo
+    .comment()
+    .get_comment(id)
+    .await?
+    .and_then(|comment| comment.like)
+    .unwrap_or(0);
+  Ok(json!({
+    "like": like_count,
+    "liked": like,
+  }))
+}
+
+pub async fn react_comment(
+  state: &AppState,
+  id: u32,
+  reaction: String,
+  react: bool,
+  token: Option<String>,
+  ip: String,
+  ua: String,
+) -> ServiceResult<Value> {
+  if !state.comment_reactions.contains(&reaction) {
+    return Err(AppError::Error);
+  }
+  let comment = state
+    .repo
+    .comment()
+    .get_comment(id)
+    .await?
+    .filter(|comment| comment.deleted_at.is_none())
+    .ok_or(AppError::Error)?;
+  let (user_id, fingerprint) = resolve_visitor(state, token, &ip, &ua).await?;
+  let visitor = match user_id {
+    Some(user_id) => VisitorBy::UserId(user_id),
+    None => VisitorBy::Fingerprint(&fingerprint),
+  };
+  if state
+    .repo
+    .reaction()
+    .set_reaction(id, &reaction, visitor.clone(), react)
+    .await?
+  {
+    state
+      .comment_cache
+      .lock()
+      .unwrap()
+      .invalidate(&comment.url.unwrap_or_default());
+  }
+  let mut reactions: BTreeMap<String, i64> = state
+    .comment_reactions
+    .iter()
+    .map(|reaction| (reaction.clone(), 0))
+    .collect();
+  for (_, reaction, count) in state
+    .repo
+    .reaction()
+    .get_reaction_counts(vec![id as i32])
+    .await?
+  {
+    if let Some(total) = reactions.get_mut(&reaction) {
+      *total = count;
+    }
+  }
+  let mine: Vec<String> = state
+    .repo
+    .reaction()
+    .get_visitor_reactions(vec![id as i32], visitor)
+    .await?
+    .into_iter()
+    .map(|(_, reaction)| reaction)
+    .collect();
+  Ok(json!({
+    "reactions": reactions,
+    "myReactions": mine,
+  }))
+}
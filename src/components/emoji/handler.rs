@@ -0,0 +1,19 @@
+use actix_web::{
+  HttpResponse, get,
+  web::{Data, Query},
+};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState,
+  components::emoji::{model::*, service},
+};
+
+#[get("/emoji")]
+pub async fn get_emoji_manifest(
+  state: Data<AppState>,
+  query: Query<GetEmojiManifestQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(GetEmojiManifestQuery { lang }) = query;
+  service::get_emoji_manifest(&state).into_http_response(Some(&lang))
+}
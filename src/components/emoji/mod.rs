@@ -0,0 +1,17 @@
+mod handler;
+mod model;
+mod service;
+
+use actix_files::Files;
+use actix_web::web::ServiceConfig;
+
+use crate::helpers::emoji::is_emoji_file;
+
+pub fn config(cfg: &mut ServiceConfig) {
+  cfg.service(handler::get_emoji_manifest);
+}
+
+/// Serve the images of the packs in `dir` under `/emoji/`
+pub fn files(cfg: &mut ServiceConfig, dir: &str) {
+  cfg.service(Files::new("/emoji", dir).path_filter(|path, _| is_emoji_file(path)));
+}
@@ -0,0 +1,9 @@
+use serde_json::{Value, json};
+
+use crate::app::AppState;
+use crate::prelude::*;
+
+/// The emoji packs for the client's picker
+pub fn get_emoji_manifest(state: &AppState) -> ServiceResult<Value> {
+  Ok(json!(state.markdown.emoji.packs))
+}
@@ -4,16 +4,18 @@ use crate::traits::LoggingResultErr;
 use crate::{
   app::AppState,
   entities::{wl_comment, wl_counter, wl_users},
+  helpers::path::normalize_path,
   prelude::AppError,
 };
 use chrono::{DateTime, Utc};
-use sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, Set};
 use serde_json::{Value, json};
 
 use super::model::{CounterData, UserData};
 
 pub async fn export_data(state: &AppState, _lang: String) -> Result<Value, String> {
   let comments = wl_comment::Entity::find()
+    .filter(wl_comment::Column::DeletedAt.is_null())
     .into_partial_model::<CommentData>()
     .all(&state.repo.db)
     .await
@@ -67,7 +69,7 @@ pub async fn create_comment_data(
     nick: Set(nick),
     status: Set(status.unwrap()),
     ua: Set(ua),
-    url: Set(url),
+    url: Set(url.map(|url| normalize_path(&url, state.path_ignore_case))),
     created_at: Set(created_at),
     updated_at: Set(updated_at),
     ..Default::default()
@@ -118,7 +120,7 @@ pub async fn create_counter_data(
       reaction6: Set(reaction6),
       reaction7: Set(reaction7),
       reaction8: Set(reaction8),
-      url: Set(url.unwrap()),
+      url: Set(normalize_path(&url.unwrap(), state.path_ignore_case)),
       created_at: Set(created_at),
       updated_at: Set(updated_at),
       ..Default::default()
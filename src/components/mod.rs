@@ -2,6 +2,11 @@
 
 pub mod article;
 pub mod comment;
+pub mod emoji;
 pub mod migration;
+pub mod path_setting;
+pub mod shadow_ban;
+pub mod stats;
+pub mod trust;
 pub mod ui;
 pub mod user;
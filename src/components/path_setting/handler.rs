@@ -0,0 +1,49 @@
+use actix_web::{
+  HttpRequest, HttpResponse, delete, get, put,
+  web::{Data, Json, Path, Query},
+};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState,
+  components::path_setting::{model::*, service},
+  helpers::header::extract_token,
+};
+
+#[get("/path-setting")]
+pub async fn get_path_setting_list(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<GetPathSettingQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(GetPathSettingQuery { lang, page }) = query;
+  service::get_path_setting_list(&state, extract_token(&req)?, page.unwrap_or(1))
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[put("/path-setting")]
+pub async fn set_path_setting(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<PathSettingQuery>,
+  body: Json<SetPathSettingBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(PathSettingQuery { lang }) = query;
+  service::set_path_setting(&state, extract_token(&req)?, body.0)
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[delete("/path-setting/{id}")]
+pub async fn delete_path_setting(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<PathSettingQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(PathSettingQuery { lang }) = query;
+  service::delete_path_setting(&state, extract_token(&req)?, path.into_inner())
+    .await
+    .into_http_response(Some(&lang))
+}
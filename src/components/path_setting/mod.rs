@@ -0,0 +1,11 @@
+mod handler;
+mod model;
+mod service;
+
+use actix_web::web::ServiceConfig;
+
+pub fn config(cfg: &mut ServiceConfig) {
+  cfg.service(handler::get_path_setting_list);
+  cfg.service(handler::set_path_setting);
+  cfg.service(handler::delete_path_setting);
+}
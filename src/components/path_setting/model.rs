@@ -0,0 +1,25 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GetPathSettingQuery {
+  pub lang: String,
+  pub page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct PathSettingQuery {
+  pub lang: String,
+}
+
+/// The full settings of a path, fields left out fall back to the defaults
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetPathSettingBody {
+  pub path: String,
+  #[serde(default)]
+  pub closed: bool,
+  /// Close the thread this many days after its first comment
+  pub auto_close_days: Option<i32>,
+  /// Overrides `COMMENT_AUDIT` for this path
+  pub comment_audit: Option<bool>,
+}
@@ -0,0 +1,65 @@
+use sea_orm::ItemsAndPagesNumber;
+use serde_json::{Value, json};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState,
+  components::path_setting::model::SetPathSettingBody,
+  helpers::{auth::verify_admin, path::normalize_path},
+};
+
+pub async fn get_path_setting_list(
+  state: &AppState,
+  token: String,
+  page: u32,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let page_size = 10;
+  let (
+    ItemsAndPagesNumber {
+      number_of_pages, ..
+    },
+    settings,
+  ) = state
+    .repo
+    .path_setting()
+    .get_settings_list(page.max(1) as u64, page_size)
+    .await?;
+  Ok(json!({
+    "data": settings,
+    "page": page,
+    "pageSize": page_size,
+    "totalPages": number_of_pages,
+  }))
+}
+
+pub async fn set_path_setting(
+  state: &AppState,
+  token: String,
+  body: SetPathSettingBody,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let SetPathSettingBody {
+    path,
+    closed,
+    auto_close_days,
+    comment_audit,
+  } = body;
+  if auto_close_days.is_some_and(|days| days < 1) {
+    return Err(AppError::Error);
+  }
+  let path = normalize_path(&path, state.path_ignore_case);
+  let setting = state
+    .repo
+    .path_setting()
+    .set_setting(path.clone(), closed, auto_close_days, comment_audit)
+    .await?;
+  state.comment_cache.lock().unwrap().invalidate(&path);
+  Ok(json!(setting))
+}
+
+pub async fn delete_path_setting(state: &AppState, token: String, id: u32) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  state.repo.path_setting().delete_setting(id).await?;
+  Ok(json!({}))
+}
@@ -0,0 +1,49 @@
+use actix_web::{
+  HttpRequest, HttpResponse, delete, get, post,
+  web::{Data, Json, Path, Query},
+};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState,
+  components::shadow_ban::{model::*, service},
+  helpers::header::extract_token,
+};
+
+#[get("/shadowban")]
+pub async fn get_shadow_ban_list(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<GetShadowBanQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(GetShadowBanQuery { lang, page }) = query;
+  service::get_shadow_ban_list(&state, extract_token(&req)?, page.unwrap_or(1))
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[post("/shadowban")]
+pub async fn create_shadow_ban(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<ShadowBanQuery>,
+  body: Json<CreateShadowBanBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(ShadowBanQuery { lang }) = query;
+  service::create_shadow_ban(&state, extract_token(&req)?, body.0)
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[delete("/shadowban/{id}")]
+pub async fn delete_shadow_ban(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<ShadowBanQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(ShadowBanQuery { lang }) = query;
+  service::delete_shadow_ban(&state, extract_token(&req)?, path.into_inner())
+    .await
+    .into_http_response(Some(&lang))
+}
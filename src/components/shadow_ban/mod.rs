@@ -0,0 +1,11 @@
+mod handler;
+mod model;
+mod service;
+
+use actix_web::web::ServiceConfig;
+
+pub fn config(cfg: &mut ServiceConfig) {
+  cfg.service(handler::get_shadow_ban_list);
+  cfg.service(handler::create_shadow_ban);
+  cfg.service(handler::delete_shadow_ban);
+}
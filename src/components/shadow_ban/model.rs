@@ -0,0 +1,19 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GetShadowBanQuery {
+  pub lang: String,
+  pub page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct ShadowBanQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateShadowBanBody {
+  pub user_id: Option<i32>,
+  pub mail: Option<String>,
+  pub ip: Option<String>,
+}
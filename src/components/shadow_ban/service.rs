@@ -0,0 +1,58 @@
+use sea_orm::ItemsAndPagesNumber;
+use serde_json::{Value, json};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState, components::shadow_ban::model::CreateShadowBanBody, helpers::auth::verify_admin,
+};
+
+pub async fn get_shadow_ban_list(
+  state: &AppState,
+  token: String,
+  page: u32,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let page_size = 10;
+  let (
+    ItemsAndPagesNumber {
+      number_of_pages, ..
+    },
+    shadow_bans,
+  ) = state
+    .repo
+    .shadow_ban()
+    .get_shadow_bans_list(page.max(1) as u64, page_size)
+    .await?;
+  Ok(json!({
+    "data": shadow_bans,
+    "page": page,
+    "pageSize": page_size,
+    "totalPages": number_of_pages,
+  }))
+}
+
+pub async fn create_shadow_ban(
+  state: &AppState,
+  token: String,
+  body: CreateShadowBanBody,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let CreateShadowBanBody { user_id, mail, ip } = body;
+  let mail = mail.filter(|mail| !mail.is_empty());
+  let ip = ip.filter(|ip| !ip.is_empty());
+  if user_id.is_none() && mail.is_none() && ip.is_none() {
+    return Err(AppError::Error);
+  }
+  let shadow_ban = state
+    .repo
+    .shadow_ban()
+    .create_shadow_ban(user_id, mail, ip)
+    .await?;
+  Ok(json!(shadow_ban))
+}
+
+pub async fn delete_shadow_ban(state: &AppState, token: String, id: u32) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  state.repo.shadow_ban().delete_shadow_ban(id).await?;
+  Ok(json!({}))
+}
@@ -0,0 +1,23 @@
+use actix_web::{
+  HttpRequest, HttpResponse, get,
+  web::{Data, Query},
+};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState,
+  components::stats::{model::*, service},
+  helpers::header::extract_token,
+};
+
+#[get("/stats")]
+pub async fn get_stats(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<GetStatsQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(GetStatsQuery { lang, days }) = query;
+  service::get_stats(&state, extract_token(&req)?, days.unwrap_or(30))
+    .await
+    .into_http_response(Some(&lang))
+}
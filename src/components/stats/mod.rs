@@ -0,0 +1,9 @@
+mod handler;
+mod model;
+mod service;
+
+use actix_web::web::ServiceConfig;
+
+pub fn config(cfg: &mut ServiceConfig) {
+  cfg.service(handler::get_stats);
+}
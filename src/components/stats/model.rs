@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GetStatsQuery {
+  pub lang: String,
+  // Number of days covered by the per-day series and the top lists
+  pub days: Option<u32>,
+}
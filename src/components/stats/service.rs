@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate};
+use helpers::time::utc_now;
+use serde_json::{Value, json};
+
+use crate::prelude::*;
+use crate::{app::AppState, helpers::auth::verify_admin};
+
+const TOP_LIMIT: u64 = 10;
+
+/// Fill the days without any entry into a series of daily counts
+fn count_per_day(counts: Vec<(String, i64)>, start: NaiveDate, end: NaiveDate) -> Value {
+  let counts: BTreeMap<String, i64> = counts.into_iter().collect();
+  let mut days = BTreeMap::new();
+  let mut day = start;
+  while day <= end {
+    days.insert(day, counts.get(&day.to_string()).copied().unwrap_or(0));
+    day += Duration::days(1);
+  }
+  json!(
+    days
+      .into_iter()
+      .map(|(date, count)| json!({ "date": date.to_string(), "count": count }))
+      .collect::<Vec<_>>()
+  )
+}
+
+pub async fn get_stats(state: &AppState, token: String, days: u32) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let days = days.clamp(1, 365);
+  let now = utc_now();
+  let end = now.date_naive();
+  let start = end - Duration::days(days as i64 - 1);
+  let since = start.and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+  let status: BTreeMap<String, i64> = state
+    .repo
+    .comment()
+    .get_status_counts()
+    .await?
+    .into_iter()
+    .collect();
+  let total: i64 = status.values().sum();
+  let comments = count_per_day(
+    state.repo.comment().get_daily_counts(since).await?,
+    start,
+    end,
+  );
+  let registrations = count_per_day(state.repo.user().get_daily_counts(since).await?, start, end);
+  let top_paths = state
+    .repo
+    .comment()
+    .get_top_paths(since, TOP_LIMIT)
+    .await?
+    .into_iter()
+    .map(|(url, count)| json!({ "url": url, "count": count }))
+    .collect::<Vec<_>>();
+  let top_commenters = state
+    .repo
+    .comment()
+    .get_top_commenters(since, TOP_LIMIT)
+    .await?
+    .into_iter()
+    .map(|(nick, mail, count)| json!({ "nick": nick, "mail": mail, "count": count }))
+    .collect::<Vec<_>>();
+  let top_views = state
+    .repo
+    .counter()
+    .get_top_counters(TOP_LIMIT)
+    .await?
+    .into_iter()
+    .map(|counter| json!({ "url": counter.url, "time": counter.time }))
+    .collect::<Vec<_>>();
+
+  Ok(json!({
+    "days": days,
+    "total": total,
+    "status": status,
+    "comments": comments,
+    "registrations": registrations,
+    "topPaths": top_paths,
+    "topCommenters": top_commenters,
+    "topViews": top_views,
+  }))
+}
@@ -0,0 +1,49 @@
+use actix_web::{
+  HttpRequest, HttpResponse, delete, get, put,
+  web::{Data, Json, Path, Query},
+};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState,
+  components::trust::{model::*, service},
+  helpers::header::extract_token,
+};
+
+#[get("/trust")]
+pub async fn get_trust_list(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<GetTrustQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(GetTrustQuery { lang, page }) = query;
+  service::get_trust_list(&state, extract_token(&req)?, page.unwrap_or(1))
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[put("/trust")]
+pub async fn set_trust(
+  req: HttpRequest,
+  state: Data<AppState>,
+  query: Query<TrustQuery>,
+  body: Json<SetTrustBody>,
+) -> Result<HttpResponse, AppError> {
+  let Query(TrustQuery { lang }) = query;
+  service::set_trust(&state, extract_token(&req)?, body.0)
+    .await
+    .into_http_response(Some(&lang))
+}
+
+#[delete("/trust/{id}")]
+pub async fn delete_trust(
+  req: HttpRequest,
+  state: Data<AppState>,
+  path: Path<u32>,
+  query: Query<TrustQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(TrustQuery { lang }) = query;
+  service::delete_trust(&state, extract_token(&req)?, path.into_inner())
+    .await
+    .into_http_response(Some(&lang))
+}
@@ -0,0 +1,11 @@
+mod handler;
+mod model;
+mod service;
+
+use actix_web::web::ServiceConfig;
+
+pub fn config(cfg: &mut ServiceConfig) {
+  cfg.service(handler::get_trust_list);
+  cfg.service(handler::set_trust);
+  cfg.service(handler::delete_trust);
+}
@@ -0,0 +1,19 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GetTrustQuery {
+  pub lang: String,
+  pub page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct TrustQuery {
+  pub lang: String,
+}
+
+#[derive(Deserialize)]
+pub struct SetTrustBody {
+  pub user_id: Option<i32>,
+  pub mail: Option<String>,
+  pub trusted: bool,
+}
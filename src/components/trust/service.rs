@@ -0,0 +1,55 @@
+use sea_orm::ItemsAndPagesNumber;
+use serde_json::{Value, json};
+
+use crate::prelude::*;
+use crate::{
+  app::AppState, components::trust::model::SetTrustBody, helpers::auth::verify_admin,
+  repository::CommentAuthorBy,
+};
+
+pub async fn get_trust_list(state: &AppState, token: String, page: u32) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let page_size = 10;
+  let (
+    ItemsAndPagesNumber {
+      number_of_pages, ..
+    },
+    trusts,
+  ) = state
+    .repo
+    .trust()
+    .get_trusts_list(page.max(1) as u64, page_size)
+    .await?;
+  Ok(json!({
+    "data": trusts,
+    "page": page,
+    "pageSize": page_size,
+    "totalPages": number_of_pages,
+  }))
+}
+
+pub async fn set_trust(
+  state: &AppState,
+  token: String,
+  body: SetTrustBody,
+) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  let SetTrustBody {
+    user_id,
+    mail,
+    trusted,
+  } = body;
+  let author = match (user_id, mail.as_deref()) {
+    (Some(user_id), _) => CommentAuthorBy::UserId(user_id),
+    (None, Some(mail)) if !mail.is_empty() => CommentAuthorBy::Mail(mail),
+    _ => return Err(AppError::Error),
+  };
+  let trust = state.repo.trust().set_trust(author, trusted).await?;
+  Ok(json!(trust))
+}
+
+pub async fn delete_trust(state: &AppState, token: String, id: u32) -> ServiceResult<Value> {
+  verify_admin(state, &token).await?;
+  state.repo.trust().delete_trust(id).await?;
+  Ok(json!({}))
+}
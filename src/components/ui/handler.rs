@@ -107,6 +107,23 @@ pub async fn ui_forgot_page(
   )
 }
 
+#[get("/redirect")]
+pub async fn ui_redirect_page(
+  state: Data<AppState>,
+  query: Query<UiRedirectPageQuery>,
+) -> Result<HttpResponse, AppError> {
+  let Query(UiRedirectPageQuery { url }) = query;
+  Ok(
+    HttpResponse::Ok()
+      .content_type(ContentType::html())
+      .body(service::redirect_page(
+        &state.site_url,
+        &state.site_name,
+        &url,
+      )),
+  )
+}
+
 pub async fn ui_page(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse, AppError> {
   let server_url = get_server_url(&req)?;
   Ok(
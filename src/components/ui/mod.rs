@@ -10,4 +10,5 @@ pub fn config(cfg: &mut ServiceConfig) {
   cfg.service(handler::ui_migration_page);
   cfg.service(handler::ui_user_page);
   cfg.service(handler::ui_forgot_page);
+  cfg.service(handler::ui_redirect_page);
 }
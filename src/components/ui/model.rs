@@ -6,6 +6,11 @@ pub struct UIProfilePageQuery {
   pub token: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct UiRedirectPageQuery {
+  pub url: String,
+}
+
 #[derive(Deserialize)]
 pub struct UiLoginPageQeury {
   pub redirect: Option<String>,
@@ -1,5 +1,7 @@
 use std::env;
 
+use ammonia::{Url, clean_text};
+
 pub async fn admin_page(site_url: &str, site_name: &str, server_url: &str) -> String {
   let recaptcha_v3_key = env::var("recaptchaV3Key")
     .ok()
@@ -28,3 +30,29 @@ pub async fn admin_page(site_url: &str, site_name: &str, server_url: &str) -> St
        </html>"#
   )
 }
+
+/// Page shown before leaving the site through an external link of a comment. Only web links are
+/// offered, anything else leads back to the site.
+pub fn redirect_page(site_url: &str, site_name: &str, url: &str) -> String {
+  let target = Url::parse(url)
+    .ok()
+    .filter(|target| matches!(target.scheme(), "http" | "https"))
+    .map_or(site_url.to_string(), |target| target.to_string());
+  let target = clean_text(&target);
+  let site_name = clean_text(site_name);
+  format!(
+    r#"<!doctype html>
+       <html>
+         <head>
+           <meta charset="utf-8">
+           <title>{site_name}</title>
+           <meta name="viewport" content="width=device-width,initial-scale=1">
+           <meta name="referrer" content="no-referrer">
+         </head>
+         <body>
+           <p>You are leaving {site_name} for an external page:</p>
+           <p><a href="{target}" rel="nofollow ugc noopener noreferrer">{target}</a></p>
+         </body>
+       </html>"#
+  )
+}
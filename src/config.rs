@@ -50,6 +50,48 @@ fn default_false() -> bool {
   false
 }
 
+fn default_true() -> bool {
+  true
+}
+
+fn default_trusted_spam_window() -> i64 {
+  30
+}
+
+fn default_report_threshold() -> u64 {
+  3
+}
+
+fn default_inline_reply_limit() -> u64 {
+  20
+}
+
+fn default_hot_decay() -> f64 {
+  1.8
+}
+
+fn default_comment_reactions() -> Vec<String> {
+  ["👍", "👎", "😄", "🎉", "😕", "❤️"]
+    .iter()
+    .map(|reaction| reaction.to_string())
+    .collect()
+}
+
+fn default_markdown_extensions() -> Vec<String> {
+  ["table", "strikethrough", "tasklist", "footnote"]
+    .iter()
+    .map(|extension| extension.to_string())
+    .collect()
+}
+
+fn default_edit_grace_period() -> i64 {
+  600
+}
+
+fn default_trash_retention_days() -> u64 {
+  30
+}
+
 fn default_oauth_url() -> String {
   "https://oauth.lithub.cc".to_string()
 }
@@ -77,6 +119,22 @@ pub struct EnvConfig {
   pub ipqps: u64,
   #[serde(default = "default_false")]
   pub comment_audit: bool,
+  #[serde(default)]
+  pub trusted_comment_count: u64,
+  #[serde(default = "default_trusted_spam_window")]
+  pub trusted_spam_window: i64,
+  #[serde(default = "default_report_threshold")]
+  pub report_threshold: u64,
+  #[serde(default = "default_inline_reply_limit")]
+  pub inline_reply_limit: u64,
+  #[serde(default = "default_hot_decay")]
+  pub hot_decay: f64,
+  #[serde(default = "default_edit_grace_period")]
+  pub edit_grace_period: i64,
+  #[serde(default = "default_false")]
+  pub path_ignore_case: bool,
+  #[serde(default = "default_trash_retention_days")]
+  pub trash_retention_days: u64,
   #[serde(default = "default_akismet_key")]
   pub akismet_key: String,
   #[serde(default = "default_login")]
@@ -87,6 +145,26 @@ pub struct EnvConfig {
   pub disallow_ip_list: Vec<String>,
   #[serde(default, deserialize_with = "deserialize_comma_separated")]
   pub forbidden_words: Vec<String>,
+  #[serde(
+    default = "default_comment_reactions",
+    deserialize_with = "deserialize_comma_separated"
+  )]
+  pub comment_reactions: Vec<String>,
+  #[serde(
+    default = "default_markdown_extensions",
+    deserialize_with = "deserialize_comma_separated"
+  )]
+  pub markdown_extensions: Vec<String>,
+  #[serde(default = "default_true")]
+  pub markdown_highlight: bool,
+  #[serde(default, deserialize_with = "deserialize_comma_separated")]
+  pub sanitize_allow_tags: Vec<String>,
+  #[serde(default, deserialize_with = "deserialize_comma_separated")]
+  pub sanitize_deny_tags: Vec<String>,
+  #[serde(default, deserialize_with = "deserialize_comma_separated")]
+  pub sanitize_allow_attributes: Vec<String>,
+  pub link_redirect: Option<String>,
+  pub emoji_dir: Option<String>,
   #[serde(default, deserialize_with = "deserialize_comma_separated")]
   pub secure_domains: Vec<String>,
   #[serde(default = "default_false")]
@@ -1,5 +1,12 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.0
 
 pub mod wl_comment;
+pub mod wl_comment_revision;
 pub mod wl_counter;
+pub mod wl_like;
+pub mod wl_path_setting;
+pub mod wl_reaction;
+pub mod wl_report;
+pub mod wl_shadow_ban;
+pub mod wl_trust;
 pub mod wl_users;
@@ -29,6 +29,11 @@ pub struct Model {
   pub created_at: Option<DateTimeUtc>,
   #[sea_orm(column_name = "updatedAt")]
   pub updated_at: Option<DateTimeUtc>,
+  #[sea_orm(column_name = "deletedAt")]
+  pub deleted_at: Option<DateTimeUtc>,
+  #[sea_orm(column_name = "editedAt")]
+  pub edited_at: Option<DateTimeUtc>,
+  pub locked: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,18 @@
+use helpers::jwt;
+
+use crate::{app::AppState, entities::wl_users, error::AppError};
+
+/// Verify the token and return the administrator it belongs to
+pub async fn verify_admin(state: &AppState, token: &str) -> Result<wl_users::Model, AppError> {
+  let email = jwt::verify::<String>(token, &state.jwt_token)?.claims.data;
+  let user = state
+    .repo
+    .user()
+    .get_user_by_email(&email)
+    .await?
+    .ok_or(AppError::UserNotFound)?;
+  if user.user_type != "administrator" {
+    return Err(AppError::Forbidden);
+  }
+  Ok(user)
+}
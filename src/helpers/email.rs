@@ -54,6 +54,9 @@ pub struct Notification<'a> {
 pub enum NotifyType {
   RegisterUser,
   NewComment,
+  ReportComment,
+  /// Someone was mentioned in a comment, holds their mail
+  Mention(String),
   _ReplyComment,
   ResetPassword,
 }
@@ -79,7 +82,7 @@ pub fn send_email_notification(notification: Notification) {
   }
   let author_email = author_email.unwrap();
   let lang = notification.lang.unwrap_or("en");
-  match notification.notify_type {
+  match &notification.notify_type {
     NotifyType::NewComment => {
       if disable_author_notify {
         return;
@@ -93,6 +96,26 @@ pub fn send_email_notification(notification: Notification) {
       to = &author_email;
       reply_to = &author_email;
     }
+    NotifyType::ReportComment => {
+      let subject_template = get_translation(lang, "MAIL_SUBJECT_REPORT");
+      let body_template = get_translation(lang, "MAIL_TEMPLATE_REPORT");
+      subject = strfmt!(&subject_template, site_name => site_name.clone()).unwrap();
+      body =
+        strfmt!(&body_template, site_url=> site_url, site_name=>site_name, nick=>notification.sender_name, comment=>notification.comment, post_url=>post_url)
+          .unwrap();
+      to = &author_email;
+      reply_to = &author_email;
+    }
+    NotifyType::Mention(recipient) => {
+      let subject_template = get_translation(lang, "MAIL_SUBJECT_MENTION");
+      let body_template = get_translation(lang, "MAIL_TEMPLATE_MENTION");
+      subject = strfmt!(&subject_template, site_name => site_name.clone(), nick => notification.sender_name.clone()).unwrap();
+      body =
+        strfmt!(&body_template, site_url=> site_url, site_name=>site_name, nick=>notification.sender_name, comment=>notification.comment, post_url=>post_url)
+          .unwrap();
+      to = recipient;
+      reply_to = &author_email;
+    }
     NotifyType::_ReplyComment => {
       subject = "".to_owned();
       body = "".to_owned();
@@ -0,0 +1,154 @@
+use std::{collections::HashMap, fs, io, path::Path, sync::LazyLock};
+
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use regex::Regex;
+use serde::Serialize;
+
+/// Image files a pack may hold, anything else in the directory is neither listed nor served
+pub const EMOJI_EXTENSIONS: [&str; 5] = ["png", "gif", "webp", "jpg", "jpeg"];
+
+/// What has to be encoded in a segment of an image url
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+  .add(b' ')
+  .add(b'"')
+  .add(b'#')
+  .add(b'%')
+  .add(b'/')
+  .add(b'<')
+  .add(b'>')
+  .add(b'?')
+  .add(b'`')
+  .add(b'{')
+  .add(b'}');
+
+/// What a shortcode may be made of, so that every emoji can be written
+static SHORTCODE_NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[\w+-]+$").unwrap());
+
+/// A piece of text once its shortcodes are expanded
+pub enum Expanded {
+  Text(String),
+  /// The `<img>` of a known shortcode
+  Emoji(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Emoji {
+  /// Shortcode without the colons, `<pack>_<file stem>`
+  pub code: String,
+  pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmojiPack {
+  pub name: String,
+  /// The first emoji of the pack, for the picker tab
+  pub icon: String,
+  pub items: Vec<Emoji>,
+}
+
+/// Emoji and sticker packs of the site. Each sub-directory of `EMOJI_DIR` is a pack and each image
+/// in it an emoji, served under `/emoji/<pack>/<file>` and written `:<pack>_<file stem>:`.
+#[derive(Debug, Clone, Default)]
+pub struct EmojiRegistry {
+  pub packs: Vec<EmojiPack>,
+  urls: HashMap<String, String>,
+}
+
+impl EmojiRegistry {
+  /// Load the packs of `dir`, with image urls under `base_url`. The images end up in comments
+  /// shown on the blog's pages, so `base_url` has to be an absolute http(s) url of the server.
+  pub fn load(dir: &str, base_url: &str) -> io::Result<Self> {
+    if !ammonia::Url::parse(base_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "SERVER_URL must be set to the absolute url of the server",
+      ));
+    }
+    let base_url = base_url.trim_end_matches('/');
+    let mut registry = EmojiRegistry::default();
+    let mut pack_dirs: Vec<_> = fs::read_dir(dir)?
+      .filter_map(Result::ok)
+      .filter(|entry| entry.path().is_dir())
+      .collect();
+    pack_dirs.sort_by_key(|entry| entry.file_name());
+    for pack_dir in pack_dirs {
+      let Some(name) = pack_dir.file_name().to_str().map(str::to_string) else {
+        continue;
+      };
+      let mut files: Vec<_> = fs::read_dir(pack_dir.path())?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_file() && is_emoji_file(&entry.path()))
+        .collect();
+      files.sort_by_key(|entry| entry.file_name());
+      let items: Vec<Emoji> = files
+        .iter()
+        .filter_map(|file| {
+          let path = file.path();
+          let stem = path.file_stem()?.to_str()?;
+          let file_name = path.file_name()?.to_str()?;
+          Some(Emoji {
+            code: format!("{name}_{stem}"),
+            url: format!(
+              "{base_url}/emoji/{}/{}",
+              utf8_percent_encode(&name, PATH_SEGMENT),
+              utf8_percent_encode(file_name, PATH_SEGMENT)
+            ),
+          })
+        })
+        .filter(|emoji| SHORTCODE_NAME.is_match(&emoji.code))
+        .collect();
+      let Some(icon) = items.first().map(|emoji| emoji.url.clone()) else {
+        continue;
+      };
+      for emoji in &items {
+        registry.urls.insert(emoji.code.clone(), emoji.url.clone());
+      }
+      registry.packs.push(EmojiPack { name, icon, items });
+    }
+    Ok(registry)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.urls.is_empty()
+  }
+
+  /// Split a text around its known `:code:` shortcodes, which become images. After a colon that
+  /// starts no known shortcode, the next colon may still start one, as in `:unknown:known:`.
+  pub fn expand(&self, text: &str) -> Vec<Expanded> {
+    let mut pieces = vec![];
+    let mut last = 0;
+    let mut from = 0;
+    while let Some(start) = text[from..].find(':').map(|offset| from + offset) {
+      let Some(length) = text[start + 1..].find(':') else {
+        break;
+      };
+      let end = start + length + 2;
+      let Some(url) = self.urls.get(&text[start + 1..end - 1]) else {
+        from = start + 1;
+        continue;
+      };
+      if last < start {
+        pieces.push(Expanded::Text(text[last..start].to_string()));
+      }
+      pieces.push(Expanded::Emoji(format!(
+        "<img class=\"wl-emoji\" src=\"{}\" alt=\"{}\">",
+        ammonia::clean_text(url),
+        &text[start..end]
+      )));
+      last = end;
+      from = end;
+    }
+    if last < text.len() {
+      pieces.push(Expanded::Text(text[last..].to_string()));
+    }
+    pieces
+  }
+}
+
+/// Whether a path is an image an emoji pack may hold
+pub fn is_emoji_file(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .is_some_and(|extension| EMOJI_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
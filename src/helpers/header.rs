@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use actix_web::HttpRequest;
 
 use crate::{config::EnvConfig, error::AppError};
@@ -14,19 +16,53 @@ pub fn extract_token(req: &HttpRequest) -> Result<String, AppError> {
   Ok(auth_header[7..].to_string()) // Skip "Bearer " prefix
 }
 
+/// Edit token handed to anonymous commenters, see `EDIT_GRACE_PERIOD`
+pub fn extract_edit_token(req: &HttpRequest) -> Option<String> {
+  req
+    .headers()
+    .get("X-Edit-Token")
+    .and_then(|h| h.to_str().ok())
+    .map(|s| s.to_string())
+}
+
+/// Address of the client. The forwarding headers are only believed when the request comes
+/// from a proxy on the same host or private network, and then only the last address of
+/// `X-Forwarded-For`, the one that proxy added, since anything before it comes from the client.
 pub fn extract_ip(req: &HttpRequest) -> String {
-  if let Some(h) = req.headers().get("X-Forwarded-For") {
-    h.to_str().unwrap_or("0.0.0.0").to_string()
-  } else if let Some(h) = req.headers().get("X-Real-IP") {
-    h.to_str().ok().unwrap_or("0.0.0.0").to_string()
-  } else {
-    req
-      .peer_addr()
-      .map(|s| s.ip().to_string())
-      .unwrap_or_default()
+  let peer = req.peer_addr().map(|addr| addr.ip().to_canonical());
+  if !peer.is_some_and(is_proxy_addr) {
+    return peer.map(|ip| ip.to_string()).unwrap_or_default();
+  }
+  let forwarded = req
+    .headers()
+    .get("X-Forwarded-For")
+    .and_then(|h| h.to_str().ok())
+    .and_then(|h| h.rsplit(',').next())
+    .or_else(|| req.headers().get("X-Real-IP").and_then(|h| h.to_str().ok()))
+    .map(str::trim)
+    .filter(|ip| !ip.is_empty());
+  match forwarded {
+    Some(ip) => ip.to_string(),
+    None => peer.map(|ip| ip.to_string()).unwrap_or_default(),
   }
 }
 
+fn is_proxy_addr(ip: IpAddr) -> bool {
+  match ip {
+    IpAddr::V4(ip) => ip.is_loopback() || ip.is_private(),
+    IpAddr::V6(ip) => ip.is_loopback() || ip.is_unique_local(),
+  }
+}
+
+pub fn extract_user_agent(req: &HttpRequest) -> String {
+  req
+    .headers()
+    .get("User-Agent")
+    .and_then(|h| h.to_str().ok())
+    .unwrap_or_default()
+    .to_string()
+}
+
 pub fn extract_referer(req: &HttpRequest) -> Option<String> {
   req
     .headers()
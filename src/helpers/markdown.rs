@@ -1,13 +1,471 @@
-use pulldown_cmark::{self, Event};
-
-/// Render markdown to html
-pub fn render_md_to_html(markdown: &str) -> String {
-  let parser = pulldown_cmark::Parser::new(markdown);
-  let parser = parser.map(|event| match event {
-    Event::SoftBreak => Event::HardBreak,
-    _ => event,
-  });
+use std::sync::{
+  Arc, LazyLock,
+  atomic::{AtomicBool, Ordering},
+};
+
+use ammonia::{Builder, Url};
+use helpers::hash;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use pulldown_cmark::{self, CodeBlockKind, CowStr, Event, Options, Tag, TagEnd};
+use syntect::{
+  html::{ClassStyle, ClassedHTMLGenerator},
+  parsing::SyntaxSet,
+  util::LinesWithEndings,
+};
+
+use crate::helpers::{
+  emoji::{EmojiRegistry, Expanded},
+  math::tex_to_mathml,
+  mention::{Commenter, Mentioned, split_mentions},
+};
+
+/// Tags of the MathML that `tex_to_mathml` writes
+const MATHML_TAGS: [&str; 15] = [
+  "math", "mrow", "mi", "mn", "mo", "mtext", "mspace", "merror", "msub", "msup", "msubsup",
+  "mfrac", "msqrt", "mroot", "mover",
+];
+
+/// Prefix of the classes highlighted code is marked up with, for themes to style
+const HIGHLIGHT_CLASS_PREFIX: &str = "hl-";
+
+/// `rel` of every link in a comment
+const LINK_REL: &str = "nofollow ugc noopener";
+
+/// Marks the names of footnotes, so their links can be told from the `#id` links of mentions
+const FOOTNOTE_MARK: &str = "fn-";
+
+/// Tags whose content is dropped by the sanitizer and that can never be allowed
+const FORBIDDEN_TAGS: [&str; 2] = ["script", "style"];
+
+/// Link attributes the sanitizer sets itself, which can never be allowed
+const LINK_ATTRIBUTES: [&str; 2] = ["rel", "target"];
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// The Markdown dialect comments are written in
+#[derive(Clone, Debug)]
+pub struct MarkdownOptions {
+  pub extensions: Options,
+  /// Highlight fenced code blocks that name their language
+  pub highlight: bool,
+  pub policy: SanitizePolicy,
+  /// Packs whose `:code:` shortcodes become images
+  pub emoji: Arc<EmojiRegistry>,
+}
+
+/// What the sanitizer lets through on top of what the Markdown dialect needs
+#[derive(Clone, Debug, Default)]
+pub struct SanitizePolicy {
+  allowed_tags: Vec<String>,
+  denied_tags: Vec<String>,
+  /// Attributes allowed on a tag, or on every tag when it is `None`
+  allowed_attributes: Vec<(Option<String>, String)>,
+  /// Prefix external links are sent through, followed by the encoded target
+  link_redirect: Option<String>,
+  /// The site, which relative links point into and whose links are not external
+  site_url: Option<Url>,
+}
+
+impl SanitizePolicy {
+  /// A policy from `SANITIZE_ALLOW_TAGS`, `SANITIZE_DENY_TAGS`, `SANITIZE_ALLOW_ATTRIBUTES`
+  /// (`tag:attribute`, or `attribute` for every tag) and `LINK_REDIRECT`. Script and style
+  /// tags and their attributes, event handler attributes, and the classes and link attributes
+  /// the renderer owns are logged and ignored.
+  pub fn new(
+    allowed_tags: Vec<String>,
+    denied_tags: Vec<String>,
+    allowed_attributes: Vec<String>,
+    link_redirect: Option<String>,
+    site_url: &str,
+  ) -> Self {
+    let allowed_tags = allowed_tags
+      .into_iter()
+      .map(|tag| tag.trim().to_lowercase())
+      .filter(|tag| {
+        let forbidden = FORBIDDEN_TAGS.contains(&tag.as_str());
+        if forbidden {
+          tracing::warn!("The {tag} tag cannot be allowed in comments");
+        }
+        !tag.is_empty() && !forbidden
+      })
+      .collect();
+    let allowed_attributes = allowed_attributes
+      .into_iter()
+      .filter_map(|entry| {
+        let entry = entry.trim().to_lowercase();
+        let (tag, attribute) = match entry.split_once(':') {
+          Some((tag, attribute)) => (Some(tag.to_string()), attribute.to_string()),
+          None => (None, entry.clone()),
+        };
+        if attribute == "class"
+          || attribute.starts_with("on")
+          || LINK_ATTRIBUTES.contains(&attribute.as_str())
+          || tag
+            .as_ref()
+            .is_some_and(|tag| FORBIDDEN_TAGS.contains(&tag.as_str()))
+        {
+          tracing::warn!("The {entry} attribute cannot be allowed in comments");
+          return None;
+        }
+        (!attribute.is_empty()).then_some((tag, attribute))
+      })
+      .collect();
+    SanitizePolicy {
+      allowed_tags,
+      denied_tags: denied_tags
+        .into_iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect(),
+      allowed_attributes,
+      link_redirect: link_redirect.filter(|prefix| !prefix.is_empty()),
+      site_url: Url::parse(site_url).ok(),
+    }
+  }
+
+  /// Where a link points once external links go through the redirect page
+  fn link_target(&self, href: &str) -> String {
+    let Some(prefix) = &self.link_redirect else {
+      return href.to_string();
+    };
+    // Resolved the way the browser will, so protocol-relative links count as external too
+    let site_host = self.site_url.as_ref().and_then(Url::host_str);
+    match Url::options().base_url(self.site_url.as_ref()).parse(href) {
+      Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str() != site_host => {
+        format!(
+          "{prefix}{}",
+          utf8_percent_encode(url.as_str(), NON_ALPHANUMERIC)
+        )
+      }
+      _ => href.to_string(),
+    }
+  }
+}
+
+impl Default for MarkdownOptions {
+  fn default() -> Self {
+    MarkdownOptions {
+      extensions: Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES,
+      highlight: true,
+      policy: SanitizePolicy::default(),
+      emoji: Arc::default(),
+    }
+  }
+}
+
+impl MarkdownOptions {
+  /// Options from the names in `MARKDOWN_EXTENSIONS`: `table`, `strikethrough`, `tasklist`,
+  /// `footnote` and `math`. Unknown names are logged and ignored.
+  pub fn from_names(names: &[String]) -> Self {
+    let mut extensions = Options::empty();
+    for name in names {
+      match name.trim() {
+        "table" => extensions |= Options::ENABLE_TABLES,
+        "strikethrough" => extensions |= Options::ENABLE_STRIKETHROUGH,
+        "tasklist" => extensions |= Options::ENABLE_TASKLISTS,
+        "footnote" => extensions |= Options::ENABLE_FOOTNOTES,
+        "math" => extensions |= Options::ENABLE_MATH,
+        "" => {}
+        name => tracing::warn!("Unknown markdown extension {name}"),
+      }
+    }
+    MarkdownOptions {
+      extensions,
+      ..Default::default()
+    }
+  }
+
+  /// The sanitizer, allowing what the enabled extensions write and what the policy allows on top
+  /// of ammonia's defaults. Footnote ids get `id_prefix`, so they stay apart from the ids of the
+  /// page and of other comments.
+  fn sanitizer<'a>(&'a self, id_prefix: &'a str) -> Builder<'a> {
+    let mut builder = Builder::default();
+    builder
+      .link_rel(Some(LINK_REL))
+      .set_tag_attribute_value("a", "target", "_blank");
+    if self.extensions.contains(Options::ENABLE_TASKLISTS) {
+      builder
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["checked"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "");
+    }
+    if self.extensions.contains(Options::ENABLE_FOOTNOTES) {
+      builder
+        .add_tag_attributes("div", ["id"])
+        .id_prefix(Some(id_prefix))
+        .add_allowed_classes("div", ["footnote-definition"])
+        .add_allowed_classes("sup", ["footnote-reference", "footnote-definition-label"]);
+    }
+    if self.extensions.contains(Options::ENABLE_MATH) {
+      builder
+        .add_tags(MATHML_TAGS)
+        .add_tag_attributes("math", ["display"])
+        .add_tag_attributes("mi", ["mathvariant"])
+        .add_tag_attributes("mo", ["stretchy"])
+        .add_tag_attributes("mspace", ["width"])
+        .add_tag_attributes("mover", ["accent"])
+        .add_tag_attributes("mfrac", ["linethickness"]);
+    }
+    if self.highlight {
+      builder
+        .add_tag_attributes("span", ["class"])
+        .add_tag_attributes("code", ["class"]);
+    }
+    builder.add_tags(&self.policy.allowed_tags);
+    for (tag, attribute) in &self.policy.allowed_attributes {
+      match tag {
+        Some(tag) => builder.add_tag_attributes(tag.as_str(), [attribute.as_str()]),
+        None => builder.add_generic_attributes([attribute.as_str()]),
+      };
+    }
+    if !self.emoji.is_empty() {
+      builder.add_allowed_classes("img", ["wl-emoji"]);
+    }
+    builder.rm_tags(&self.policy.denied_tags);
+    let highlight = self.highlight;
+    let policy = self.policy.clone();
+    let footnote_link = format!("#{FOOTNOTE_MARK}");
+    let id_prefix = id_prefix.to_string();
+    // The `target` the sanitizer adds comes after the `href` of the same link, so in-page links
+    // like mentions and footnotes can be left to open where they are
+    let in_page = AtomicBool::new(false);
+    builder.attribute_filter(
+      move |element, attribute, value| match (element, attribute) {
+        ("a", "target") if in_page.swap(false, Ordering::Relaxed) => None,
+        ("span", "class") if highlight => Some(keep_classes(value, HIGHLIGHT_CLASS_PREFIX).into()),
+        ("code", "class") if highlight => Some(keep_classes(value, "language-").into()),
+        ("a", "href") => {
+          in_page.store(value.starts_with('#'), Ordering::Relaxed);
+          if value.starts_with(&footnote_link) {
+            Some(format!("#{id_prefix}{}", &value[1..]).into())
+          } else {
+            Some(policy.link_target(value).into())
+          }
+        }
+        _ => Some(value.into()),
+      },
+    );
+    builder
+  }
+}
+
+/// The classes of a class attribute that start with `prefix`
+fn keep_classes(value: &str, prefix: &str) -> String {
+  value
+    .split_whitespace()
+    .filter(|class| class.starts_with(prefix))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// A code block highlighted for `language`, or `None` when the language is not known
+fn highlight_code(language: &str, code: &str) -> Option<String> {
+  let syntax = SYNTAX_SET
+    .find_syntax_by_token(language)
+    .filter(|_| !language.is_empty())?;
+  let mut generator = ClassedHTMLGenerator::new_with_class_style(
+    syntax,
+    &SYNTAX_SET,
+    ClassStyle::SpacedPrefixed {
+      prefix: HIGHLIGHT_CLASS_PREFIX,
+    },
+  );
+  for line in LinesWithEndings::from(code) {
+    generator
+      .parse_html_for_line_which_includes_newline(line)
+      .ok()?;
+  }
+  Some(format!(
+    "<pre><code class=\"language-{}\">{}</code></pre>\n",
+    ammonia::clean_text(language),
+    generator.finalize()
+  ))
+}
+
+/// Replace fenced code blocks in a known language by their highlighted html
+fn highlight_code_blocks<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+  let mut output = vec![];
+  // Language and events of the fenced code block being read
+  let mut block: Option<(String, Vec<Event<'a>>)> = None;
+  for event in events {
+    match (&mut block, event) {
+      (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) => {
+        let language = info
+          .split(|c: char| c.is_whitespace() || c == ',')
+          .next()
+          .unwrap_or_default()
+          .to_string();
+        block = Some((
+          language,
+          vec![Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))],
+        ));
+      }
+      (Some((language, events)), Event::End(TagEnd::CodeBlock)) => {
+        let code: String = events
+          .iter()
+          .filter_map(|event| match event {
+            Event::Text(text) => Some(text.as_ref()),
+            _ => None,
+          })
+          .collect();
+        match highlight_code(language, &code) {
+          Some(html) => output.push(Event::Html(CowStr::from(html))),
+          None => {
+            output.append(events);
+            output.push(Event::End(TagEnd::CodeBlock));
+          }
+        }
+        block = None;
+      }
+      (Some((_, events)), event) => events.push(event),
+      (None, event) => output.push(event),
+    }
+  }
+  output
+}
+
+/// Replace the known emoji shortcodes of the text outside code by their image
+fn expand_emoji<'a>(events: Vec<Event<'a>>, emoji: &EmojiRegistry) -> Vec<Event<'a>> {
+  let mut output = vec![];
+  let mut in_code_block = false;
+  for event in events {
+    match event {
+      Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+      Event::End(TagEnd::CodeBlock) => in_code_block = false,
+      Event::Text(text) if !in_code_block => {
+        output.extend(emoji.expand(&text).into_iter().map(|piece| match piece {
+          Expanded::Text(text) => Event::Text(CowStr::from(text)),
+          Expanded::Emoji(html) => Event::InlineHtml(CowStr::from(html)),
+        }));
+        continue;
+      }
+      _ => {}
+    }
+    output.push(event);
+  }
+  output
+}
+
+/// Link every `@nick` of the `commenters` to their latest comment, and return the commenters
+/// that were mentioned. Only text is looked at, not code, and not the text of links and
+/// images. Adjacent pieces of text are joined first, so a nick split by the parser is still
+/// found.
+fn link_mentions<'a, 'c>(
+  events: Vec<Event<'a>>,
+  commenters: &'c [Commenter],
+) -> (Vec<Event<'a>>, Vec<&'c Commenter>) {
+  let mut output = vec![];
+  let mut mentioned: Vec<&Commenter> = vec![];
+  let mut text = String::new();
+  let mut in_code_block = false;
+  let mut in_link = 0;
+  let mut flush = |text: &mut String, output: &mut Vec<Event<'a>>| {
+    if text.is_empty() {
+      return;
+    }
+    for piece in split_mentions(text, commenters) {
+      output.push(match piece {
+        Mentioned::Text(text) => Event::Text(CowStr::from(text)),
+        Mentioned::Mention(commenter) => {
+          if !mentioned
+            .iter()
+            .any(|known| known.comment_id == commenter.comment_id)
+          {
+            mentioned.push(commenter);
+          }
+          Event::InlineHtml(CowStr::from(format!(
+            "<a href=\"#{}\">@{}</a>",
+            commenter.comment_id,
+            ammonia::clean_text(&commenter.nick)
+          )))
+        }
+      });
+    }
+    text.clear();
+  };
+  for event in events {
+    if let Event::Text(piece) = &event
+      && !in_code_block
+      && in_link == 0
+    {
+      text.push_str(piece);
+      continue;
+    }
+    flush(&mut text, &mut output);
+    match event {
+      Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+      Event::End(TagEnd::CodeBlock) => in_code_block = false,
+      Event::Start(Tag::Link { .. } | Tag::Image { .. }) => in_link += 1,
+      Event::End(TagEnd::Link | TagEnd::Image) => in_link -= 1,
+      _ => {}
+    }
+    output.push(event);
+  }
+  flush(&mut text, &mut output);
+  (output, mentioned)
+}
+
+fn parse_markdown<'a>(markdown: &'a str, options: &MarkdownOptions) -> Vec<Event<'a>> {
+  pulldown_cmark::Parser::new_ext(markdown, options.extensions)
+    .map(|event| match event {
+      Event::SoftBreak => Event::HardBreak,
+      Event::FootnoteReference(name) => {
+        Event::FootnoteReference(CowStr::from(format!("{FOOTNOTE_MARK}{name}")))
+      }
+      Event::Start(Tag::FootnoteDefinition(name)) => Event::Start(Tag::FootnoteDefinition(
+        CowStr::from(format!("{FOOTNOTE_MARK}{name}")),
+      )),
+      Event::InlineMath(tex) => Event::InlineHtml(CowStr::from(tex_to_mathml(&tex, false))),
+      Event::DisplayMath(tex) => Event::Html(CowStr::from(tex_to_mathml(&tex, true))),
+      _ => event,
+    })
+    .collect()
+}
+
+/// The `commenters` a comment mentions
+pub fn find_mentions<'c>(
+  markdown: &str,
+  options: &MarkdownOptions,
+  commenters: &'c [Commenter],
+) -> Vec<&'c Commenter> {
+  link_mentions(parse_markdown(markdown, options), commenters).1
+}
+
+/// Render markdown to html, with the mentions of `commenters` linked
+pub fn render_md_to_html(
+  markdown: &str,
+  options: &MarkdownOptions,
+  commenters: &[Commenter],
+) -> String {
+  let mut events = parse_markdown(markdown, options);
+  if !commenters.is_empty() {
+    events = link_mentions(events, commenters).0;
+  }
+  if !options.emoji.is_empty() {
+    events = expand_emoji(events, &options.emoji);
+  }
+  if options.highlight {
+    events = highlight_code_blocks(events.into_iter());
+  }
   let mut html_output = String::new();
-  pulldown_cmark::html::push_html(&mut html_output, parser);
+  pulldown_cmark::html::push_html(&mut html_output, events.into_iter());
   html_output
 }
+
+/// Render a comment to the html readers get: markdown, then sanitization. This is the only way
+/// comment html is made, so every response carries the same safe markup.
+pub fn render_comment(
+  markdown: &str,
+  options: &MarkdownOptions,
+  commenters: &[Commenter],
+) -> String {
+  let id_prefix = format!("wl-{}-", &hash::md5(markdown)[..8]);
+  options
+    .sanitizer(&id_prefix)
+    .clean(&render_md_to_html(markdown, options, commenters))
+    .to_string()
+}
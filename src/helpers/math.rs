@@ -0,0 +1,652 @@
+/// Longest formula that is rendered, in characters
+const MAX_FORMULA_LENGTH: usize = 2000;
+/// Deepest nesting of groups and commands that is rendered. The parser is recursive, so this
+/// keeps a formula like `\sqrt\sqrt…x` from overflowing the stack.
+const MAX_NESTING_DEPTH: usize = 50;
+
+/// Render a TeX formula to MathML. Covers the subset comments use: scripts, fractions, roots,
+/// Greek letters, common operators and arrows, accents, `\left`/`\right` and text and font
+/// commands. Unknown commands are shown as an `<merror>` instead of failing the whole formula,
+/// and so are formulas that are too long or nested too deeply.
+pub fn tex_to_mathml(tex: &str, display: bool) -> String {
+  let chars: Vec<char> = tex.chars().collect();
+  let content = if chars.len() > MAX_FORMULA_LENGTH {
+    format!("<merror><mtext>{}</mtext></merror>", escape(tex))
+  } else {
+    let mut parser = Parser {
+      chars,
+      pos: 0,
+      depth: 0,
+    };
+    parser.parse_list(false, false).concat()
+  };
+  format!(
+    "<math display=\"{}\"><mrow>{content}</mrow></math>",
+    if display { "block" } else { "inline" },
+  )
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+fn greek(name: &str) -> Option<char> {
+  Some(match name {
+    "alpha" => 'α',
+    "beta" => 'β',
+    "gamma" => 'γ',
+    "delta" => 'δ',
+    "epsilon" => 'ϵ',
+    "varepsilon" => 'ε',
+    "zeta" => 'ζ',
+    "eta" => 'η',
+    "theta" => 'θ',
+    "vartheta" => 'ϑ',
+    "iota" => 'ι',
+    "kappa" => 'κ',
+    "lambda" => 'λ',
+    "mu" => 'μ',
+    "nu" => 'ν',
+    "xi" => 'ξ',
+    "pi" => 'π',
+    "varpi" => 'ϖ',
+    "rho" => 'ρ',
+    "varrho" => 'ϱ',
+    "sigma" => 'σ',
+    "varsigma" => 'ς',
+    "tau" => 'τ',
+    "upsilon" => 'υ',
+    "phi" => 'ϕ',
+    "varphi" => 'φ',
+    "chi" => 'χ',
+    "psi" => 'ψ',
+    "omega" => 'ω',
+    "Gamma" => 'Γ',
+    "Delta" => 'Δ',
+    "Theta" => 'Θ',
+    "Lambda" => 'Λ',
+    "Xi" => 'Ξ',
+    "Pi" => 'Π',
+    "Sigma" => 'Σ',
+    "Upsilon" => 'Υ',
+    "Phi" => 'Φ',
+    "Psi" => 'Ψ',
+    "Omega" => 'Ω',
+    _ => return None,
+  })
+}
+
+fn operator(name: &str) -> Option<&'static str> {
+  Some(match name {
+    "times" => "×",
+    "cdot" => "⋅",
+    "div" => "÷",
+    "pm" => "±",
+    "mp" => "∓",
+    "ast" => "∗",
+    "star" => "⋆",
+    "circ" => "∘",
+    "bullet" => "∙",
+    "le" | "leq" => "≤",
+    "ge" | "geq" => "≥",
+    "ne" | "neq" => "≠",
+    "ll" => "≪",
+    "gg" => "≫",
+    "approx" => "≈",
+    "equiv" => "≡",
+    "sim" => "∼",
+    "simeq" => "≃",
+    "cong" => "≅",
+    "propto" => "∝",
+    "in" => "∈",
+    "notin" => "∉",
+    "ni" => "∋",
+    "subset" => "⊂",
+    "subseteq" => "⊆",
+    "supset" => "⊃",
+    "supseteq" => "⊇",
+    "cup" => "∪",
+    "cap" => "∩",
+    "setminus" => "∖",
+    "emptyset" | "varnothing" => "∅",
+    "forall" => "∀",
+    "exists" => "∃",
+    "neg" | "lnot" => "¬",
+    "land" | "wedge" => "∧",
+    "lor" | "vee" => "∨",
+    "oplus" => "⊕",
+    "otimes" => "⊗",
+    "to" | "rightarrow" => "→",
+    "leftarrow" | "gets" => "←",
+    "leftrightarrow" => "↔",
+    "Rightarrow" | "implies" => "⇒",
+    "Leftarrow" => "⇐",
+    "Leftrightarrow" | "iff" => "⇔",
+    "mapsto" => "↦",
+    "uparrow" => "↑",
+    "downarrow" => "↓",
+    "sum" => "∑",
+    "prod" => "∏",
+    "coprod" => "∐",
+    "int" => "∫",
+    "iint" => "∬",
+    "iiint" => "∭",
+    "oint" => "∮",
+    "bigcup" => "⋃",
+    "bigcap" => "⋂",
+    "partial" => "∂",
+    "nabla" => "∇",
+    "infty" => "∞",
+    "angle" => "∠",
+    "perp" => "⊥",
+    "parallel" => "∥",
+    "mid" => "∣",
+    "ldots" | "dots" => "…",
+    "cdots" => "⋯",
+    "vdots" => "⋮",
+    "ddots" => "⋱",
+    "prime" => "′",
+    "hbar" => "ℏ",
+    "ell" => "ℓ",
+    "Re" => "ℜ",
+    "Im" => "ℑ",
+    "aleph" => "ℵ",
+    "langle" => "⟨",
+    "rangle" => "⟩",
+    "lfloor" => "⌊",
+    "rfloor" => "⌋",
+    "lceil" => "⌈",
+    "rceil" => "⌉",
+    "lbrace" => "{",
+    "rbrace" => "}",
+    "vert" => "|",
+    "Vert" => "‖",
+    _ => return None,
+  })
+}
+
+fn function(name: &str) -> bool {
+  matches!(
+    name,
+    "sin"
+      | "cos"
+      | "tan"
+      | "cot"
+      | "sec"
+      | "csc"
+      | "arcsin"
+      | "arccos"
+      | "arctan"
+      | "sinh"
+      | "cosh"
+      | "tanh"
+      | "log"
+      | "ln"
+      | "lg"
+      | "exp"
+      | "lim"
+      | "liminf"
+      | "limsup"
+      | "max"
+      | "min"
+      | "sup"
+      | "inf"
+      | "det"
+      | "gcd"
+      | "deg"
+      | "dim"
+      | "ker"
+      | "arg"
+      | "Pr"
+  )
+}
+
+fn accent(name: &str) -> Option<&'static str> {
+  Some(match name {
+    "hat" | "widehat" => "^",
+    "bar" | "overline" => "¯",
+    "vec" => "→",
+    "dot" => "˙",
+    "ddot" => "¨",
+    "tilde" | "widetilde" => "~",
+    _ => return None,
+  })
+}
+
+fn variant(name: &str) -> Option<&'static str> {
+  Some(match name {
+    "mathrm" | "operatorname" => "normal",
+    "mathbf" => "bold",
+    "mathit" => "italic",
+    "mathbb" => "double-struck",
+    "mathcal" => "script",
+    "mathfrak" => "fraktur",
+    "mathsf" => "sans-serif",
+    "mathtt" => "monospace",
+    _ => return None,
+  })
+}
+
+fn space(name: &str) -> Option<&'static str> {
+  Some(match name {
+    "," => "0.167em",
+    ":" | ">" => "0.222em",
+    ";" => "0.278em",
+    " " => "0.333em",
+    "quad" => "1em",
+    "qquad" => "2em",
+    _ => return None,
+  })
+}
+
+struct Parser {
+  chars: Vec<char>,
+  pos: usize,
+  /// Atoms and commands being parsed, one inside the other
+  depth: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.pos).copied()
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self.peek().is_some_and(char::is_whitespace) {
+      self.pos += 1;
+    }
+  }
+
+  fn at_right(&self) -> bool {
+    let rest = &self.chars[self.pos..];
+    rest.starts_with(&['\\', 'r', 'i', 'g', 'h', 't'])
+      && !rest.get(6).is_some_and(|c| c.is_ascii_alphabetic())
+  }
+
+  /// Atoms up to the end, up to the closing brace of the current group, or up to the `\right`
+  /// that closes the current `\left`
+  fn parse_list(&mut self, in_group: bool, in_left: bool) -> Vec<String> {
+    let mut nodes = vec![];
+    loop {
+      self.skip_whitespace();
+      match self.peek() {
+        None => break,
+        Some('}') if in_group => {
+          self.pos += 1;
+          break;
+        }
+        Some('\\') if in_left && self.at_right() => break,
+        Some(_) => {
+          if let Some(node) = self.parse_scripted() {
+            nodes.push(node);
+          }
+        }
+      }
+    }
+    nodes
+  }
+
+  /// An atom with its subscript and superscript, if any
+  fn parse_scripted(&mut self) -> Option<String> {
+    let base = self.parse_atom()?;
+    let mut sub = None;
+    let mut sup = None;
+    loop {
+      self.skip_whitespace();
+      match self.peek() {
+        Some('_') if sub.is_none() => {
+          self.pos += 1;
+          sub = Some(self.parse_argument());
+        }
+        Some('^') if sup.is_none() => {
+          self.pos += 1;
+          sup = Some(self.parse_argument());
+        }
+        Some('\'') if sup.is_none() => {
+          let mut primes = String::new();
+          while self.peek() == Some('\'') {
+            self.pos += 1;
+            primes.push('′');
+          }
+          sup = Some(format!("<mo>{primes}</mo>"));
+        }
+        _ => break,
+      }
+    }
+    Some(match (sub, sup) {
+      (None, None) => base,
+      (Some(sub), None) => format!("<msub>{base}{sub}</msub>"),
+      (None, Some(sup)) => format!("<msup>{base}{sup}</msup>"),
+      (Some(sub), Some(sup)) => format!("<msubsup>{base}{sub}{sup}</msubsup>"),
+    })
+  }
+
+  /// A single atom or a braced group, as one node
+  fn parse_argument(&mut self) -> String {
+    self.skip_whitespace();
+    self
+      .parse_atom()
+      .unwrap_or_else(|| "<mrow></mrow>".to_string())
+  }
+
+  /// Raw text of a braced argument, for `\text` and friends
+  fn parse_text_argument(&mut self) -> String {
+    self.skip_whitespace();
+    if self.peek() != Some('{') {
+      return self.chars.get(self.pos).map_or(String::new(), |c| {
+        self.pos += 1;
+        c.to_string()
+      });
+    }
+    self.pos += 1;
+    let mut depth = 0;
+    let mut text = String::new();
+    while let Some(c) = self.peek() {
+      self.pos += 1;
+      match c {
+        '{' => depth += 1,
+        '}' if depth == 0 => break,
+        '}' => depth -= 1,
+        _ => {}
+      }
+      text.push(c);
+    }
+    text
+  }
+
+  /// Enter one more level of nesting. Past the limit the rest of the formula is given up on
+  /// and shown as an error.
+  fn nest(&mut self) -> Result<(), String> {
+    if self.depth >= MAX_NESTING_DEPTH {
+      let rest: String = self.chars[self.pos..].iter().collect();
+      self.pos = self.chars.len();
+      return Err(format!("<merror><mtext>{}</mtext></merror>", escape(&rest)));
+    }
+    self.depth += 1;
+    Ok(())
+  }
+
+  fn parse_atom(&mut self) -> Option<String> {
+    if let Err(error) = self.nest() {
+      return Some(error);
+    }
+    let atom = self.parse_nested_atom();
+    self.depth -= 1;
+    atom
+  }
+
+  fn parse_nested_atom(&mut self) -> Option<String> {
+    let c = self.peek()?;
+    self.pos += 1;
+    Some(match c {
+      '{' => format!("<mrow>{}</mrow>", self.parse_list(true, false).concat()),
+      '\\' => return self.parse_command(),
+      '0'..='9' | '.' => {
+        let mut number = c.to_string();
+        while let Some(next) = self
+          .peek()
+          .filter(|next| next.is_ascii_digit() || *next == '.')
+        {
+          number.push(next);
+          self.pos += 1;
+        }
+        format!("<mn>{number}</mn>")
+      }
+      '}' | '^' | '_' => return None,
+      '&' | '~' => "<mspace width=\"0.333em\"/>".to_string(),
+      c if c.is_alphabetic() => format!("<mi>{c}</mi>"),
+      c => format!("<mo>{}</mo>", escape(&c.to_string())),
+    })
+  }
+
+  fn parse_command(&mut self) -> Option<String> {
+    if let Err(error) = self.nest() {
+      return Some(error);
+    }
+    let command = self.parse_nested_command();
+    self.depth -= 1;
+    command
+  }
+
+  fn parse_nested_command(&mut self) -> Option<String> {
+    let start = self.pos;
+    while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+      self.pos += 1;
+    }
+    if self.pos == start {
+      // A single symbol after the backslash
+      let c = self.peek()?;
+      self.pos += 1;
+      return Some(match space(&c.to_string()) {
+        Some(width) => format!("<mspace width=\"{width}\"/>"),
+        None if c == '!' || c == '\\' => String::new(),
+        None => format!("<mo>{}</mo>", escape(&c.to_string())),
+      });
+    }
+    let name: String = self.chars[start..self.pos].iter().collect();
+    Some(match name.as_str() {
+      "frac" | "dfrac" | "tfrac" | "binom" => {
+        let numerator = self.parse_argument();
+        let denominator = self.parse_argument();
+        if name == "binom" {
+          format!(
+            "<mrow><mo>(</mo><mfrac linethickness=\"0\">{numerator}{denominator}</mfrac><mo>)</mo></mrow>"
+          )
+        } else {
+          format!("<mfrac>{numerator}{denominator}</mfrac>")
+        }
+      }
+      "sqrt" => {
+        self.skip_whitespace();
+        if self.peek() == Some('[') {
+          self.pos += 1;
+          let mut index = vec![];
+          loop {
+            self.skip_whitespace();
+            match self.peek() {
+              None => break,
+              Some(']') => {
+                self.pos += 1;
+                break;
+              }
+              Some(_) => index.extend(self.parse_scripted()),
+            }
+          }
+          let radicand = self.parse_argument();
+          format!("<mroot>{radicand}<mrow>{}</mrow></mroot>", index.concat())
+        } else {
+          format!("<msqrt>{}</msqrt>", self.parse_argument())
+        }
+      }
+      "text" | "textrm" | "mbox" => {
+        format!("<mtext>{}</mtext>", escape(&self.parse_text_argument()))
+      }
+      "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" => {
+        self.skip_whitespace();
+        let delimiter = match self.peek() {
+          Some('\\') => {
+            self.pos += 1;
+            self.parse_command().unwrap_or_default()
+          }
+          Some('.') => {
+            self.pos += 1;
+            String::new()
+          }
+          Some(c) => {
+            self.pos += 1;
+            format!("<mo>{}</mo>", escape(&c.to_string()))
+          }
+          None => String::new(),
+        };
+        let delimiter = delimiter.replace("<mo>", "<mo stretchy=\"true\">");
+        if name == "left" {
+          let inner = self.parse_list(false, true).concat();
+          // The matching `\right` and its delimiter
+          let right = if self.peek() == Some('\\') {
+            self.pos += 1;
+            self.parse_command().unwrap_or_default()
+          } else {
+            String::new()
+          };
+          format!("<mrow>{delimiter}{inner}{right}</mrow>")
+        } else {
+          delimiter
+        }
+      }
+      _ => self.parse_symbol(&name),
+    })
+  }
+
+  /// Letters, operators, functions, spaces, accents and fonts, by command name
+  fn parse_symbol(&mut self, name: &str) -> String {
+    if let Some(letter) = greek(name) {
+      if letter.is_uppercase() {
+        format!("<mi mathvariant=\"normal\">{letter}</mi>")
+      } else {
+        format!("<mi>{letter}</mi>")
+      }
+    } else if let Some(symbol) = operator(name) {
+      format!("<mo>{}</mo>", escape(symbol))
+    } else if function(name) {
+      format!("<mi>{name}</mi>")
+    } else if let Some(width) = space(name) {
+      format!("<mspace width=\"{width}\"/>")
+    } else if let Some(mark) = accent(name) {
+      let base = self.parse_argument();
+      format!("<mover accent=\"true\">{base}<mo>{mark}</mo></mover>")
+    } else if let Some(variant) = variant(name) {
+      let text = self.parse_text_argument();
+      if name == "operatorname" {
+        format!("<mi>{}</mi>", escape(&text))
+      } else {
+        format!("<mi mathvariant=\"{variant}\">{}</mi>", escape(text.trim()))
+      }
+    } else {
+      format!("<merror><mtext>\\{}</mtext></merror>", escape(name))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn inline(mathml: &str) -> String {
+    format!("<math display=\"inline\"><mrow>{mathml}</mrow></math>")
+  }
+
+  fn nested_groups(depth: usize) -> String {
+    format!("{}x{}", "{".repeat(depth), "}".repeat(depth))
+  }
+
+  #[test]
+  fn renders_up_to_the_nesting_limit() {
+    let depth = MAX_NESTING_DEPTH - 1;
+    let expected = format!(
+      "{}<mi>x</mi>{}",
+      "<mrow>".repeat(depth),
+      "</mrow>".repeat(depth)
+    );
+    assert_eq!(
+      tex_to_mathml(&nested_groups(depth), false),
+      inline(&expected)
+    );
+  }
+
+  #[test]
+  fn stops_past_the_nesting_limit() {
+    let mathml = tex_to_mathml(&nested_groups(MAX_NESTING_DEPTH), false);
+    assert!(mathml.contains("<merror><mtext>x"));
+    assert!(!mathml.contains("<mi>x</mi>"));
+  }
+
+  #[test]
+  fn deep_commands_do_not_overflow_the_stack() {
+    let tex = format!("{}x", "\\sqrt".repeat((MAX_FORMULA_LENGTH - 1) / 5));
+    let mathml = tex_to_mathml(&tex, false);
+    assert_eq!(mathml.matches("<msqrt>").count(), MAX_NESTING_DEPTH / 2);
+    assert!(mathml.contains("<merror>"));
+    let tex = format!("{}x", "\\left".repeat((MAX_FORMULA_LENGTH - 1) / 5));
+    assert!(tex_to_mathml(&tex, true).contains("<merror>"));
+  }
+
+  #[test]
+  fn renders_formulas_at_the_length_limit() {
+    let tex = "x".repeat(MAX_FORMULA_LENGTH);
+    assert_eq!(
+      tex_to_mathml(&tex, false),
+      inline(&"<mi>x</mi>".repeat(MAX_FORMULA_LENGTH))
+    );
+  }
+
+  #[test]
+  fn shows_longer_formulas_as_an_error() {
+    let tex = format!("{}<", "x".repeat(MAX_FORMULA_LENGTH));
+    assert_eq!(
+      tex_to_mathml(&tex, false),
+      inline(&format!(
+        "<merror><mtext>{}&lt;</mtext></merror>",
+        "x".repeat(MAX_FORMULA_LENGTH)
+      ))
+    );
+  }
+
+  #[test]
+  fn closes_unbalanced_braces() {
+    assert_eq!(
+      tex_to_mathml("{x", false),
+      inline("<mrow><mi>x</mi></mrow>")
+    );
+    assert_eq!(tex_to_mathml("x}", false), inline("<mi>x</mi>"));
+    assert_eq!(tex_to_mathml("}{", false), inline("<mrow></mrow>"));
+    assert_eq!(
+      tex_to_mathml("\\text{a{b}", false),
+      inline("<mtext>a{b}</mtext>")
+    );
+  }
+
+  #[test]
+  fn fills_in_missing_arguments() {
+    assert_eq!(
+      tex_to_mathml("\\frac", false),
+      inline("<mfrac><mrow></mrow><mrow></mrow></mfrac>")
+    );
+    assert_eq!(
+      tex_to_mathml("\\frac{a}", false),
+      inline("<mfrac><mrow><mi>a</mi></mrow><mrow></mrow></mfrac>")
+    );
+    assert_eq!(
+      tex_to_mathml("x^", false),
+      inline("<msup><mi>x</mi><mrow></mrow></msup>")
+    );
+    assert_eq!(
+      tex_to_mathml("x_", false),
+      inline("<msub><mi>x</mi><mrow></mrow></msub>")
+    );
+    assert_eq!(tex_to_mathml("^_", false), inline(""));
+  }
+
+  #[test]
+  fn escapes_markup() {
+    assert_eq!(
+      tex_to_mathml("a<b", false),
+      inline("<mi>a</mi><mo>&lt;</mo><mi>b</mi>")
+    );
+    assert_eq!(
+      tex_to_mathml("\\text{a&b<c>\"}", false),
+      inline("<mtext>a&amp;b&lt;c&gt;&quot;</mtext>")
+    );
+    assert_eq!(
+      tex_to_mathml("\\foo<", false),
+      inline("<merror><mtext>\\foo</mtext></merror><mo>&lt;</mo>")
+    );
+    assert_eq!(
+      tex_to_mathml("\\mathbf{<&>}", false),
+      inline("<mi mathvariant=\"bold\">&lt;&amp;&gt;</mi>")
+    );
+  }
+}
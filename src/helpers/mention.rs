@@ -0,0 +1,67 @@
+/// Someone who commented in a thread and can be mentioned there
+#[derive(Debug, Clone)]
+pub struct Commenter {
+  pub nick: String,
+  pub mail: Option<String>,
+  /// Their latest comment in the thread, which a mention links to
+  pub comment_id: u32,
+}
+
+/// A piece of text once its mentions are found
+pub enum Mentioned<'a> {
+  Text(String),
+  Mention(&'a Commenter),
+}
+
+/// A mention may not continue a word, so mail addresses are left alone
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Split a text around every `@nick` of a known commenter. Longer nicks win, so `@Ann Lee`
+/// beats `@Ann`.
+pub fn split_mentions<'a>(text: &str, commenters: &'a [Commenter]) -> Vec<Mentioned<'a>> {
+  let mut candidates: Vec<&Commenter> = commenters
+    .iter()
+    .filter(|commenter| !commenter.nick.is_empty())
+    .collect();
+  candidates.sort_by_key(|commenter| std::cmp::Reverse(commenter.nick.len()));
+
+  let mut pieces = vec![];
+  let mut plain = String::new();
+  let mut previous = None;
+  let mut rest = text;
+  while let Some(c) = rest.chars().next() {
+    let linkable = c == '@' && !previous.is_some_and(is_word_char);
+    let found = linkable
+      .then(|| {
+        candidates.iter().find(|commenter| {
+          rest[1..].starts_with(&commenter.nick)
+            && !rest[1 + commenter.nick.len()..]
+              .chars()
+              .next()
+              .is_some_and(|next| next.is_alphanumeric() || next == '_')
+        })
+      })
+      .flatten();
+    match found {
+      Some(commenter) => {
+        if !plain.is_empty() {
+          pieces.push(Mentioned::Text(std::mem::take(&mut plain)));
+        }
+        pieces.push(Mentioned::Mention(commenter));
+        rest = &rest[1 + commenter.nick.len()..];
+        previous = commenter.nick.chars().last();
+      }
+      None => {
+        plain.push(c);
+        rest = &rest[c.len_utf8()..];
+        previous = Some(c);
+      }
+    }
+  }
+  if !plain.is_empty() {
+    pieces.push(Mentioned::Text(plain));
+  }
+  pieces
+}
@@ -1,9 +1,14 @@
 //! helpers
 
+pub mod auth;
 pub mod avatar;
 pub mod email;
+pub mod emoji;
 pub mod header;
 pub mod ip;
 pub mod markdown;
+pub mod math;
+pub mod mention;
+pub mod path;
 pub mod spam;
 pub mod ua;
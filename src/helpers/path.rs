@@ -0,0 +1,31 @@
+use percent_encoding::percent_decode_str;
+
+/// Canonical form of an article path, so that every spelling of a page shares its comments and
+/// counters. Full URLs are reduced to their path, the query string and fragment are dropped,
+/// percent-encoding is decoded, and `index.html` and trailing or repeated slashes go away.
+/// With `ignore_case` the path is also lowercased.
+pub fn normalize_path(path: &str, ignore_case: bool) -> String {
+  let mut path = path.trim();
+  if !path.starts_with('/')
+    && let Some((_, rest)) = path.split_once("://")
+  {
+    path = rest.find('/').map_or("/", |start| &rest[start..]);
+  }
+  let path = path.split(['?', '#']).next().unwrap_or_default();
+  let decoded = percent_decode_str(path)
+    .decode_utf8()
+    .map_or_else(|_| path.to_string(), |decoded| decoded.into_owned());
+  let mut segments: Vec<&str> = decoded
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect();
+  if matches!(segments.last(), Some(&("index.html" | "index.htm"))) {
+    segments.pop();
+  }
+  let path = format!("/{}", segments.join("/"));
+  if ignore_case {
+    path.to_lowercase()
+  } else {
+    path
+  }
+}
@@ -17,6 +17,10 @@ fn zh_cn() -> TranslationMap {
   m.insert("Unauthorized", "Unauthorized");
   m.insert("MAIL_SUBJECT_ADMIN", "{site_name} 上有新评论了");
   m.insert("MAIL_TEMPLATE_ADMIN", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> 您在<a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a>上的文章有了新的评论 </h2> <p><strong>{nick}</strong>回复说：</p><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p>您可以点击<a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>查看回复的完整內容</a></p><br/> </div>");
+  m.insert("MAIL_SUBJECT_REPORT", "{site_name} 上有评论被举报了");
+  m.insert("MAIL_TEMPLATE_REPORT", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> 您在<a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a>上的一条评论被多次举报，已转为待审核 </h2> <p><strong>{nick}</strong>的评论：</p><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p>您可以点击<a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>查看完整內容</a></p><br/> </div>");
+  m.insert("MAIL_SUBJECT_MENTION", "{nick} 在 {site_name} 上提到了您");
+  m.insert("MAIL_TEMPLATE_MENTION", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> <strong>{nick}</strong>在<a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a>上的评论中提到了您 </h2><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p>您可以点击<a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>查看完整內容</a></p><br/> </div>");
   m.insert("Registration Confirm Mail", "【{name}】注册确认邮件");
   m.insert(
     "confirm registration",
@@ -44,6 +48,10 @@ fn zh_tw() -> TranslationMap {
   m.insert("Unauthorized", "Unauthorized");
   m.insert("MAIL_SUBJECT_ADMIN", "{site_name} 上有新評論了");
   m.insert("MAIL_TEMPLATE_ADMIN", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> 您在<a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a>上的文章有新評論了 </h2> <p><strong>{nick}</strong>回復說：</p><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p>您可以點擊<a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>查看回復的完整內容</a></p><br/> </div>");
+  m.insert("MAIL_SUBJECT_REPORT", "{site_name} 上有評論被舉報了");
+  m.insert("MAIL_TEMPLATE_REPORT", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> 您在<a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a>上的一條評論被多次舉報，已轉為待審核 </h2> <p><strong>{nick}</strong>的評論：</p><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p>您可以點擊<a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>查看完整內容</a></p><br/> </div>");
+  m.insert("MAIL_SUBJECT_MENTION", "{nick} 在 {site_name} 上提到了您");
+  m.insert("MAIL_TEMPLATE_MENTION", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> <strong>{nick}</strong>在<a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a>上的評論中提到了您 </h2><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p>您可以點擊<a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>查看完整內容</a></p><br/> </div>");
   m.insert("Registration Confirm Mail", "『{name}』註冊確認郵件");
   m.insert(
     "confirm registration",
@@ -80,6 +88,16 @@ fn en() -> TranslationMap {
   m.insert("Unauthorized", "Unauthorized");
   m.insert("MAIL_SUBJECT_ADMIN", "New comment on {site_name}");
   m.insert("MAIL_TEMPLATE_ADMIN", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> New comment on <a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a> </h2> <p><strong>{nick}</strong> wrote:</p><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p><a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>View page</a></p><br/></div>");
+  m.insert(
+    "MAIL_SUBJECT_REPORT",
+    "A comment was reported on {site_name}",
+  );
+  m.insert("MAIL_TEMPLATE_REPORT", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> A comment on <a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a> was reported by readers and is waiting for review </h2> <p><strong>{nick}</strong> wrote:</p><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p><a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>View page</a></p><br/></div>");
+  m.insert(
+    "MAIL_SUBJECT_MENTION",
+    "{nick} mentioned you on {site_name}",
+  );
+  m.insert("MAIL_TEMPLATE_MENTION", "<div style='border-top:2px solid #12ADDB;box-shadow:0 1px 3px #AAAAAA;line-height:180%;padding:0 15px 12px;margin:50px auto;font-size:12px;'> <h2 style='border-bottom:1px solid #DDD;font-size:14px;font-weight:normal;padding:13px 0 10px 8px;'> <strong>{nick}</strong> mentioned you on <a style='text-decoration:none;color: #12ADDB;' href='{site_url}' target='_blank'>{site_name}</a> </h2><div style='background-color: #f5f5f5;padding: 10px 15px;margin:18px 0;word-wrap:break-word;'>{comment}</div><p><a style='text-decoration:none; color:#12addb' href='{post_url}' target='_blank'>View page</a></p><br/></div>");
   m.insert(
     "Registration Confirm Mail",
     "[{name}] Registration Confirm Mail",
@@ -0,0 +1,103 @@
+use crate::entities::{wl_comment, wl_counter, wl_path_setting};
+use crate::repository::counter::merge_counters;
+use sea_orm::{
+  ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Order, PaginatorTrait, QueryFilter,
+  QueryOrder, TransactionTrait, sea_query::Expr,
+};
+
+/// What happens to the counter of a path that is moved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CounterMove {
+  /// The old path had no counter
+  None,
+  /// The counter follows the comments to the new path
+  Rename,
+  /// Both paths had a counter and they are added up
+  Merge,
+}
+
+/// What moving one path did, or would do
+#[derive(Debug, Clone)]
+pub struct PathMove {
+  pub from: String,
+  pub to: String,
+  pub comments: u64,
+  pub counter: CounterMove,
+}
+
+/// Comments and counters of an article, as addressed by its path
+#[derive(Debug, Clone)]
+pub struct ArticleRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl ArticleRepository<'_> {
+  /// Move the comments and counter of each `from` path to its `to` path, in order and in one
+  /// transaction. A dry run reports the same moves and rolls everything back.
+  pub async fn move_paths(
+    &self,
+    mappings: &[(String, String)],
+    dry_run: bool,
+  ) -> Result<Vec<PathMove>, DbErr> {
+    let txn = self.db.begin().await?;
+    let mut moves = vec![];
+    for (from, to) in mappings {
+      let comments = wl_comment::Entity::find()
+        .filter(wl_comment::Column::Url.eq(from))
+        .count(&txn)
+        .await?;
+      let counters = wl_counter::Entity::find()
+        .filter(wl_counter::Column::Url.is_in([from, to]))
+        .order_by(wl_counter::Column::Id, Order::Asc)
+        .all(&txn)
+        .await?;
+      let counter = if !counters.iter().any(|counter| counter.url == *from) {
+        CounterMove::None
+      } else if counters.iter().any(|counter| counter.url == *to) {
+        CounterMove::Merge
+      } else {
+        CounterMove::Rename
+      };
+      if from != to {
+        wl_comment::Entity::update_many()
+          .col_expr(wl_comment::Column::Url, Expr::value(to))
+          .filter(wl_comment::Column::Url.eq(from))
+          .exec(&txn)
+          .await?;
+        if counter != CounterMove::None {
+          merge_counters(&txn, &counters, to.clone()).await?;
+        }
+        // Settings follow the article unless the new path has its own
+        let has_settings = wl_path_setting::Entity::find()
+          .filter(wl_path_setting::Column::Url.eq(to))
+          .count(&txn)
+          .await?
+          > 0;
+        if has_settings {
+          wl_path_setting::Entity::delete_many()
+            .filter(wl_path_setting::Column::Url.eq(from))
+            .exec(&txn)
+            .await?;
+        } else {
+          wl_path_setting::Entity::update_many()
+            .col_expr(wl_path_setting::Column::Url, Expr::value(to))
+            .filter(wl_path_setting::Column::Url.eq(from))
+            .exec(&txn)
+            .await?;
+        }
+      }
+      moves.push(PathMove {
+        from: from.clone(),
+        to: to.clone(),
+        comments,
+        counter,
+      });
+    }
+    if dry_run {
+      txn.rollback().await?;
+    } else {
+      txn.commit().await?;
+    }
+    Ok(moves)
+  }
+}
@@ -1,9 +1,320 @@
-use crate::entities::wl_comment;
+use crate::{
+  entities::{wl_comment, wl_comment_revision, wl_like, wl_reaction, wl_report},
+  repository::day_of,
+};
+use helpers::time::utc_now;
 use sea_orm::{
-  ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, DeleteResult, EntityTrait,
-  ItemsAndPagesNumber, Order, PaginatorTrait, QueryFilter, QueryOrder,
+  ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+  EntityTrait, ItemsAndPagesNumber, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+  QueryTrait, Select, TransactionTrait, Value,
+  prelude::DateTimeUtc,
+  sea_query::{Alias, Expr, Query, SimpleExpr},
 };
 
+#[derive(Debug, Clone)]
+pub enum CommentAuthorBy<'a> {
+  UserId(i32),
+  Mail(&'a str),
+}
+
+/// Statuses that are never shown to ordinary readers
+const HIDDEN_STATUS: [&str; 3] = ["waiting", "spam", "shadowban"];
+
+/// A shadow-banned reader, who keeps seeing their own hidden comments
+#[derive(Debug, Clone)]
+pub enum ShadowViewer<'a> {
+  UserId(i32),
+  /// An anonymous commenter, by the comments listed in their identity cookie
+  Comments(&'a [u32]),
+}
+
+/// Readers see visible comments, and a shadow-banned viewer also sees their own hidden ones
+fn visible_condition(viewer: Option<ShadowViewer<'_>>) -> Condition {
+  let condition = Condition::any().add(wl_comment::Column::Status.is_not_in(HIDDEN_STATUS));
+  let shadow = Condition::all().add(wl_comment::Column::Status.eq("shadowban"));
+  match viewer {
+    Some(ShadowViewer::UserId(user_id)) => {
+      condition.add(shadow.add(wl_comment::Column::UserId.eq(user_id)))
+    }
+    Some(ShadowViewer::Comments(ids)) => {
+      condition.add(shadow.add(wl_comment::Column::Id.is_in(ids.iter().copied())))
+    }
+    None => condition,
+  }
+}
+
+/// Criteria shared by the admin tools that select many comments at once
+#[derive(Debug, Clone, Default)]
+pub struct CommentFilter {
+  pub ids: Option<Vec<u32>>,
+  pub status: Option<String>,
+  pub keyword: Option<String>,
+  pub ip: Option<String>,
+  pub mail: Option<String>,
+  pub user_id: Option<i32>,
+  pub path: Option<String>,
+  pub start: Option<DateTimeUtc>,
+  pub end: Option<DateTimeUtc>,
+  pub has_replies: Option<bool>,
+}
+
+impl CommentFilter {
+  pub fn is_empty(&self) -> bool {
+    self.ids.is_none()
+      && self.status.is_none()
+      && self.keyword.is_none()
+      && self.ip.is_none()
+      && self.mail.is_none()
+      && self.user_id.is_none()
+      && self.path.is_none()
+      && self.start.is_none()
+      && self.end.is_none()
+      && self.has_replies.is_none()
+  }
+
+  fn condition(&self) -> Condition {
+    let mut condition = Condition::all();
+    if let Some(ids) = &self.ids {
+      condition = condition.add(wl_comment::Column::Id.is_in(ids.clone()));
+    }
+    // Trashed comments are only listed when the trash itself is asked for
+    if self.status.as_deref() == Some("deleted") {
+      condition = condition.add(wl_comment::Column::DeletedAt.is_not_null());
+    } else {
+      condition = condition.add(wl_comment::Column::DeletedAt.is_null());
+    }
+    match self.status.as_deref() {
+      Some("deleted") | None => {}
+      Some("reported") => {
+        condition = condition.add(
+          wl_comment::Column::Id.in_subquery(
+            Query::select()
+              .column(wl_report::Column::CommentId)
+              .from(wl_report::Entity)
+              .to_owned(),
+          ),
+        )
+      }
+      Some(status) => condition = condition.add(wl_comment::Column::Status.eq(status)),
+    }
+    if let Some(keyword) = &self.keyword {
+      condition = condition.add(wl_comment::Column::Comment.contains(keyword));
+    }
+    if let Some(ip) = &self.ip {
+      condition = condition.add(wl_comment::Column::Ip.eq(ip));
+    }
+    if let Some(mail) = &self.mail {
+      condition = condition.add(wl_comment::Column::Mail.eq(mail));
+    }
+    if let Some(user_id) = self.user_id {
+      condition = condition.add(wl_comment::Column::UserId.eq(user_id));
+    }
+    if let Some(path) = &self.path {
+      condition = condition.add(wl_comment::Column::Url.eq(path));
+    }
+    if let Some(start) = self.start {
+      condition = condition.add(wl_comment::Column::InsertedAt.gte(start));
+    }
+    if let Some(end) = self.end {
+      condition = condition.add(wl_comment::Column::InsertedAt.lte(end));
+    }
+    if let Some(has_replies) = self.has_replies {
+      let parents = Query::select()
+        .column(wl_comment::Column::Pid)
+        .from(wl_comment::Entity)
+        .and_where(wl_comment::Column::Pid.is_not_null())
+        .to_owned();
+      condition = condition.add(if has_replies {
+        wl_comment::Column::Id.in_subquery(parents)
+      } else {
+        wl_comment::Column::Id.not_in_subquery(parents)
+      });
+    }
+    condition
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BulkAction {
+  Approve,
+  Spam,
+  /// Move to the trash, or purge for good when the filter selects the trash
+  Delete,
+  /// Take comments out of the trash, or give comments marked as spam back to readers
+  Restore,
+}
+
+/// Collect the given comments and every reply below them whose `deletedAt` equals `deleted_at`
+async fn collect_thread<C: ConnectionTrait>(
+  conn: &C,
+  ids: Vec<u32>,
+  deleted_at: Option<DateTimeUtc>,
+) -> Result<Vec<u32>, DbErr> {
+  let mut thread = ids.clone();
+  let mut parents = ids;
+  while !parents.is_empty() {
+    let mut select = wl_comment::Entity::find()
+      .select_only()
+      .column(wl_comment::Column::Id)
+      .filter(wl_comment::Column::Pid.is_in(parents.iter().map(|id| *id as i32)));
+    select = match deleted_at {
+      Some(deleted_at) => select.filter(wl_comment::Column::DeletedAt.eq(deleted_at)),
+      None => select.filter(wl_comment::Column::DeletedAt.is_null()),
+    };
+    let children: Vec<u32> = select.into_tuple().all(conn).await?;
+    parents = children
+      .into_iter()
+      .filter(|id| !thread.contains(id))
+      .collect();
+    thread.extend(&parents);
+  }
+  Ok(thread)
+}
+
+/// Replies go to the trash together with their parent, so a thread is never left dangling
+async fn trash_comments<C: ConnectionTrait>(conn: &C, ids: Vec<u32>) -> Result<u64, DbErr> {
+  let thread = collect_thread(conn, ids, None).await?;
+  let res = wl_comment::Entity::update_many()
+    .col_expr(wl_comment::Column::DeletedAt, Expr::value(utc_now()))
+    .filter(wl_comment::Column::Id.is_in(thread))
+    .filter(wl_comment::Column::DeletedAt.is_null())
+    .exec(conn)
+    .await?;
+  Ok(res.rows_affected)
+}
+
+/// Restore the replies trashed along with each comment, and any trashed parent above it
+async fn restore_comments<C: ConnectionTrait>(conn: &C, ids: Vec<u32>) -> Result<u64, DbErr> {
+  let mut restored = vec![];
+  for id in ids {
+    let Some(comment) = wl_comment::Entity::find_by_id(id).one(conn).await? else {
+      continue;
+    };
+    let Some(deleted_at) = comment.deleted_at else {
+      continue;
+    };
+    restored.extend(collect_thread(conn, vec![id], Some(deleted_at)).await?);
+    let mut pid = comment.pid;
+    while let Some(parent_id) = pid {
+      let Some(parent) = wl_comment::Entity::find_by_id(parent_id as u32)
+        .one(conn)
+        .await?
+      else {
+        break;
+      };
+      if parent.deleted_at.is_some() {
+        restored.push(parent.id);
+      }
+      pid = parent.pid;
+    }
+  }
+  let res = wl_comment::Entity::update_many()
+    .col_expr(
+      wl_comment::Column::DeletedAt,
+      Expr::value(Option::<DateTimeUtc>::None),
+    )
+    .filter(wl_comment::Column::Id.is_in(restored))
+    .exec(conn)
+    .await?;
+  Ok(res.rows_affected)
+}
+
+/// Delete comments for good, with the reports, revisions, likes and reactions that point at them
+async fn purge_comments<C: ConnectionTrait>(conn: &C, ids: Vec<u32>) -> Result<u64, DbErr> {
+  let comment_ids = || ids.iter().map(|id| *id as i32);
+  wl_report::Entity::delete_many()
+    .filter(wl_report::Column::CommentId.is_in(comment_ids()))
+    .exec(conn)
+    .await?;
+  wl_comment_revision::Entity::delete_many()
+    .filter(wl_comment_revision::Column::CommentId.is_in(comment_ids()))
+    .exec(conn)
+    .await?;
+  wl_like::Entity::delete_many()
+    .filter(wl_like::Column::CommentId.is_in(comment_ids()))
+    .exec(conn)
+    .await?;
+  wl_reaction::Entity::delete_many()
+    .filter(wl_reaction::Column::CommentId.is_in(comment_ids()))
+    .exec(conn)
+    .await?;
+  let res = wl_comment::Entity::delete_many()
+    .filter(wl_comment::Column::Id.is_in(ids))
+    .exec(conn)
+    .await?;
+  Ok(res.rows_affected)
+}
+
+/// Pinned comments come before everything else whatever the sort order
+fn sticky_first() -> SimpleExpr {
+  Expr::col(wl_comment::Column::Sticky).if_null(0)
+}
+
+/// Column and direction of a reader sort order
+fn root_sort_key(sort_by: &str) -> (SimpleExpr, Order) {
+  match sort_by {
+    "insertedAt_asc" => (Expr::col(wl_comment::Column::InsertedAt).into(), Order::Asc),
+    "like_desc" => (Expr::col(wl_comment::Column::Like).if_null(0), Order::Desc),
+    _ => (
+      Expr::col(wl_comment::Column::InsertedAt).into(),
+      Order::Desc,
+    ),
+  }
+}
+
+/// Id, likes, sticky flag and insertion time of a root comment
+pub type RootCommentStats = (u32, Option<i32>, Option<i8>, Option<DateTimeUtc>);
+
+/// Where a page of root comments ended: sticky flag, sort key and id of its last comment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RootCursor {
+  pub sticky: i8,
+  pub key: i64,
+  pub id: u32,
+}
+
+impl RootCursor {
+  pub fn of(comment: &wl_comment::Model, sort_by: &str) -> Self {
+    let key = match sort_by {
+      "like_desc" => comment.like.unwrap_or(0) as i64,
+      _ => comment
+        .inserted_at
+        .and_then(|inserted_at| inserted_at.timestamp_nanos_opt())
+        .unwrap_or(0),
+    };
+    Self {
+      sticky: comment.sticky.unwrap_or(0),
+      key,
+      id: comment.id,
+    }
+  }
+
+  /// Rows that sort after this cursor
+  fn condition(&self, sort_by: &str) -> Condition {
+    let (key, order) = root_sort_key(sort_by);
+    let value: Value = match sort_by {
+      "like_desc" => (self.key as i32).into(),
+      _ => DateTimeUtc::from_timestamp_nanos(self.key).into(),
+    };
+    let after = |expr: SimpleExpr, value: Value| match order {
+      Order::Desc => Expr::expr(expr).lt(value),
+      _ => Expr::expr(expr).gt(value),
+    };
+    Condition::any()
+      .add(Expr::expr(sticky_first()).lt(self.sticky))
+      .add(
+        Condition::all()
+          .add(Expr::expr(sticky_first()).eq(self.sticky))
+          .add(Condition::any().add(after(key.clone(), value.clone())).add(
+            Condition::all().add(Expr::expr(key).eq(value)).add(after(
+              Expr::col(wl_comment::Column::Id).into(),
+              self.id.into(),
+            )),
+          )),
+      )
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct CommentRepository<'a> {
   pub db: &'a DatabaseConnection,
@@ -17,6 +328,63 @@ impl CommentRepository<'_> {
       .await
   }
 
+  /// Whether a reply to `pid` under the root `rid` would land in a locked sub-thread, that is
+  /// whether the root or any comment on the way up from `pid` is locked
+  pub async fn is_reply_locked(&self, pid: Option<i32>, rid: Option<i32>) -> Result<bool, DbErr> {
+    let mut visited = vec![];
+    let mut next = pid.or(rid);
+    while let Some(id) = next {
+      if visited.contains(&id) {
+        break;
+      }
+      visited.push(id);
+      let Some(comment) = self.get_comment(id as u32).await? else {
+        break;
+      };
+      if comment.locked {
+        return Ok(true);
+      }
+      next = comment.pid;
+    }
+    match rid {
+      Some(rid) if !visited.contains(&rid) => Ok(
+        self
+          .get_comment(rid as u32)
+          .await?
+          .is_some_and(|comment| comment.locked),
+      ),
+      _ => Ok(false),
+    }
+  }
+
+  /// The comments among `ids` that are shadow banned
+  pub async fn get_shadow_banned_ids(&self, ids: &[u32]) -> Result<Vec<u32>, DbErr> {
+    wl_comment::Entity::find()
+      .select_only()
+      .column(wl_comment::Column::Id)
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Status.eq("shadowban"))
+      .filter(wl_comment::Column::Id.is_in(ids.iter().copied()))
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  /// A comment that is not in the trash and that the reader is allowed to see
+  pub async fn get_visible_comment(
+    &self,
+    id: u32,
+    is_admin: bool,
+    viewer: Option<ShadowViewer<'_>>,
+  ) -> Result<Option<wl_comment::Model>, DbErr> {
+    let mut select =
+      wl_comment::Entity::find_by_id(id).filter(wl_comment::Column::DeletedAt.is_null());
+    if !is_admin {
+      select = select.filter(visible_condition(viewer));
+    }
+    select.one(self.db).await
+  }
+
   pub async fn get_comments_for_user(
     &self,
     path: &str,
@@ -24,11 +392,14 @@ impl CommentRepository<'_> {
     sort_ord: Order,
     page: u64,
     page_size: u64,
+    viewer: Option<ShadowViewer<'_>>,
   ) -> Result<(ItemsAndPagesNumber, Vec<wl_comment::Model>), DbErr> {
     let paginator = wl_comment::Entity::find()
-      .filter(wl_comment::Column::Url.contains(path))
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Url.eq(path))
       .filter(wl_comment::Column::Pid.is_null())
-      .filter(wl_comment::Column::Status.is_not_in(["waiting", "spam"]))
+      .filter(visible_condition(viewer))
+      .order_by(sticky_first(), Order::Desc)
       .order_by(sort_col, sort_ord)
       .paginate(self.db, page_size);
     let parrent_comments = paginator.fetch_page(page - 1).await?;
@@ -44,8 +415,10 @@ impl CommentRepository<'_> {
     page_size: u64,
   ) -> Result<(ItemsAndPagesNumber, Vec<wl_comment::Model>), DbErr> {
     let paginator = wl_comment::Entity::find()
-      .filter(wl_comment::Column::Url.contains(path))
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Url.eq(path))
       .filter(wl_comment::Column::Pid.is_null())
+      .order_by(sticky_first(), Order::Desc)
       .order_by(sort_col, sort_ord)
       .paginate(self.db, page_size);
     let parrent_comments = paginator.fetch_page(page - 1).await?;
@@ -59,6 +432,7 @@ impl CommentRepository<'_> {
     page: u64,
     page_size: u64,
     is_admin: bool,
+    viewer: Option<ShadowViewer<'_>>,
   ) -> Result<(ItemsAndPagesNumber, Vec<wl_comment::Model>), DbErr> {
     let (sort_col, sort_ord) = match sort_by.as_str() {
       "insertedAt_asc" => (wl_comment::Column::InsertedAt, Order::Asc),
@@ -71,11 +445,167 @@ impl CommentRepository<'_> {
         .await
     } else {
       self
-        .get_comments_for_user(path, sort_col, sort_ord, page, page_size)
+        .get_comments_for_user(path, sort_col, sort_ord, page, page_size, viewer)
         .await
     }
   }
 
+  fn root_comments(
+    &self,
+    path: &str,
+    is_admin: bool,
+    viewer: Option<ShadowViewer<'_>>,
+  ) -> Select<wl_comment::Entity> {
+    let select = wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Url.eq(path))
+      .filter(wl_comment::Column::Pid.is_null());
+    if is_admin {
+      select
+    } else {
+      select.filter(visible_condition(viewer))
+    }
+  }
+
+  /// Id, likes, sticky flag and insertion time of every root comment of a path, for sort orders
+  /// that are computed outside the database
+  pub async fn get_root_comment_stats(
+    &self,
+    path: &str,
+    is_admin: bool,
+    viewer: Option<ShadowViewer<'_>>,
+  ) -> Result<Vec<RootCommentStats>, DbErr> {
+    self
+      .root_comments(path, is_admin, viewer)
+      .select_only()
+      .column(wl_comment::Column::Id)
+      .column(wl_comment::Column::Like)
+      .column(wl_comment::Column::Sticky)
+      .column(wl_comment::Column::InsertedAt)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  /// The comments with these ids, in no particular order
+  pub async fn get_comments_by_ids(&self, ids: Vec<u32>) -> Result<Vec<wl_comment::Model>, DbErr> {
+    wl_comment::Entity::find()
+      .filter(wl_comment::Column::Id.is_in(ids))
+      .all(self.db)
+      .await
+  }
+
+  /// Keyset pagination over root comments: the page after `cursor`, the total number of root
+  /// comments and the cursor of the next page if there is one
+  pub async fn get_root_comments_after(
+    &self,
+    path: &str,
+    sort_by: &str,
+    cursor: Option<RootCursor>,
+    limit: u64,
+    is_admin: bool,
+    viewer: Option<ShadowViewer<'_>>,
+  ) -> Result<(u64, Vec<wl_comment::Model>, Option<RootCursor>), DbErr> {
+    let select = self.root_comments(path, is_admin, viewer);
+    let count = select.clone().count(self.db).await?;
+    let (key, order) = root_sort_key(sort_by);
+    let mut comments = select
+      .apply_if(cursor, |select, cursor| {
+        select.filter(cursor.condition(sort_by))
+      })
+      .order_by(sticky_first(), Order::Desc)
+      .order_by(key, order.clone())
+      .order_by(wl_comment::Column::Id, order)
+      .limit(limit + 1)
+      .all(self.db)
+      .await?;
+    let next = if comments.len() as u64 > limit {
+      comments.truncate(limit as usize);
+      comments
+        .last()
+        .map(|comment| RootCursor::of(comment, sort_by))
+    } else {
+      None
+    };
+    Ok((count, comments, next))
+  }
+
+  /// Id, nick and mail behind the visible comments of a thread, latest first
+  pub async fn get_thread_commenters(
+    &self,
+    path: &str,
+  ) -> Result<Vec<(u32, Option<String>, Option<String>)>, DbErr> {
+    wl_comment::Entity::find()
+      .select_only()
+      .column(wl_comment::Column::Id)
+      .column(wl_comment::Column::Nick)
+      .column(wl_comment::Column::Mail)
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Url.eq(path))
+      .filter(visible_condition(None))
+      .order_by(wl_comment::Column::InsertedAt, Order::Desc)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  /// When the first comment of a thread that is still around was written
+  pub async fn get_first_comment_time(&self, path: &str) -> Result<Option<DateTimeUtc>, DbErr> {
+    let first: Option<Option<DateTimeUtc>> = wl_comment::Entity::find()
+      .select_only()
+      .column_as(wl_comment::Column::InsertedAt.min(), "first")
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Url.eq(path))
+      .into_tuple()
+      .one(self.db)
+      .await?;
+    Ok(first.flatten())
+  }
+
+  /// Latest approved comments across the site
+  pub async fn get_recent_comments(&self, count: u64) -> Result<Vec<wl_comment::Model>, DbErr> {
+    wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Status.eq("approved"))
+      .order_by(wl_comment::Column::InsertedAt, Order::Desc)
+      .limit(count)
+      .all(self.db)
+      .await
+  }
+
+  /// Number of visible comments on each of `paths`, paths without comments are left out
+  pub async fn get_comment_counts_by_paths(
+    &self,
+    paths: Vec<String>,
+  ) -> Result<Vec<(Option<String>, i64)>, DbErr> {
+    wl_comment::Entity::find()
+      .select_only()
+      .column(wl_comment::Column::Url)
+      .column_as(wl_comment::Column::Id.count(), "count")
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Url.is_in(paths))
+      .filter(visible_condition(None))
+      .group_by(wl_comment::Column::Url)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  /// Number of visible replies in the thread of each root comment
+  pub async fn get_reply_counts(&self, root_ids: Vec<i32>) -> Result<Vec<(i32, i64)>, DbErr> {
+    wl_comment::Entity::find()
+      .select_only()
+      .column(wl_comment::Column::Rid)
+      .column_as(wl_comment::Column::Id.count(), "count")
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Rid.is_in(root_ids))
+      .filter(visible_condition(None))
+      .group_by(wl_comment::Column::Rid)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
   pub async fn create_comment(
     &self,
     comment: wl_comment::ActiveModel,
@@ -90,8 +620,33 @@ impl CommentRepository<'_> {
     comment.update(self.db).await
   }
 
-  pub async fn delete_comment(&self, id: u32) -> Result<DeleteResult, DbErr> {
-    wl_comment::Entity::delete_by_id(id).exec(self.db).await
+  pub async fn trash_comment(&self, id: u32) -> Result<u64, DbErr> {
+    let txn = self.db.begin().await?;
+    let count = trash_comments(&txn, vec![id]).await?;
+    txn.commit().await?;
+    Ok(count)
+  }
+
+  pub async fn restore_comment(&self, id: u32) -> Result<u64, DbErr> {
+    let txn = self.db.begin().await?;
+    let count = restore_comments(&txn, vec![id]).await?;
+    txn.commit().await?;
+    Ok(count)
+  }
+
+  /// Remove comments that have been in the trash since before `before`
+  pub async fn purge_trash(&self, before: DateTimeUtc) -> Result<u64, DbErr> {
+    let txn = self.db.begin().await?;
+    let ids: Vec<u32> = wl_comment::Entity::find()
+      .select_only()
+      .column(wl_comment::Column::Id)
+      .filter(wl_comment::Column::DeletedAt.lt(before))
+      .into_tuple()
+      .all(&txn)
+      .await?;
+    let count = purge_comments(&txn, ids).await?;
+    txn.commit().await?;
+    Ok(count)
   }
 
   pub async fn is_comment_owner(&self, id: u32, user_id: u32) -> Result<bool, DbErr> {
@@ -114,6 +669,7 @@ impl CommentRepository<'_> {
     comment: &str,
   ) -> Result<bool, DbErr> {
     let res = wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
       .filter(wl_comment::Column::Url.eq(url))
       .filter(wl_comment::Column::Mail.eq(mail))
       .filter(wl_comment::Column::Nick.eq(nick))
@@ -124,9 +680,11 @@ impl CommentRepository<'_> {
     Ok(!res.is_empty())
   }
 
+  /// Whether a comment was posted without an account. `user_id` is an integer column, so it is
+  /// only tested for null: comparing it with `''` as well matched every row on SQLite.
   pub async fn is_anonymous(&self, comment_id: u32) -> Result<bool, DbErr> {
     let res = wl_comment::Entity::find_by_id(comment_id)
-      .filter(wl_comment::Column::UserId.is_not_null().ne(""))
+      .filter(wl_comment::Column::UserId.is_not_null())
       .one(self.db)
       .await?;
     Ok(res.is_none())
@@ -138,47 +696,215 @@ impl CommentRepository<'_> {
     mail: Option<String>,
   ) -> Result<u64, DbErr> {
     wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
       .filter(wl_comment::Column::Nick.eq(nick))
       .filter(wl_comment::Column::Mail.eq(mail))
       .count(self.db)
       .await
   }
 
+  pub async fn get_comment_count_by_author(
+    &self,
+    author: CommentAuthorBy<'_>,
+    status: &str,
+    since: Option<DateTimeUtc>,
+  ) -> Result<u64, DbErr> {
+    let mut select = wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Status.eq(status));
+    match author {
+      CommentAuthorBy::UserId(user_id) => {
+        select = select.filter(wl_comment::Column::UserId.eq(user_id))
+      }
+      CommentAuthorBy::Mail(mail) => select = select.filter(wl_comment::Column::Mail.eq(mail)),
+    }
+    if let Some(since) = since {
+      select = select.filter(wl_comment::Column::InsertedAt.gte(since));
+    }
+    select.count(self.db).await
+  }
+
+  /// Replies to a comment, oldest first, from `offset` on, with the number of all replies
   pub async fn get_subcomments(
     &self,
     path: &str,
     pid: u32,
     is_admin: bool,
-  ) -> Result<Vec<wl_comment::Model>, DbErr> {
+    viewer: Option<ShadowViewer<'_>>,
+    offset: u64,
+    limit: Option<u64>,
+  ) -> Result<(u64, Vec<wl_comment::Model>), DbErr> {
     let mut select = wl_comment::Entity::find()
-      .filter(wl_comment::Column::Url.contains(path))
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Url.eq(path))
       .filter(wl_comment::Column::Pid.eq(pid))
       .order_by(wl_comment::Column::InsertedAt, Order::Asc);
 
     if !is_admin {
-      select = select.filter(wl_comment::Column::Status.is_not_in(["waiting", "spam"]));
+      select = select.filter(visible_condition(viewer));
     }
 
-    select.all(self.db).await
+    let count = select.clone().count(self.db).await?;
+    let subcomments = select.offset(offset).limit(limit).all(self.db).await?;
+    Ok((count, subcomments))
   }
 
   pub async fn get_comments_list_by_admin(
     &self,
-    email: &str,
-    status: &str,
-    keyword: &str,
+    filter: &CommentFilter,
+    sort_col: wl_comment::Column,
+    sort_ord: Order,
     page: u64,
     page_size: u64,
-    owner: String,
   ) -> Result<(ItemsAndPagesNumber, Vec<wl_comment::Model>), DbErr> {
-    let mut select = wl_comment::Entity::find()
-      .filter(wl_comment::Column::Status.eq(status))
-      .filter(wl_comment::Column::Comment.contains(keyword));
-    if owner == "mine" {
-      select = select.filter(wl_comment::Column::Mail.eq(email))
-    }
-    let paginator = select.paginate(self.db, page_size);
+    let paginator = wl_comment::Entity::find()
+      .filter(filter.condition())
+      .order_by(sort_col, sort_ord)
+      .order_by(wl_comment::Column::Id, Order::Desc)
+      .paginate(self.db, page_size);
     let comment = paginator.fetch_page(page - 1).await?;
     Ok((paginator.num_items_and_pages().await?, comment))
   }
+
+  /// Apply an action to every matching comment in one transaction and return how many comments
+  /// it changed, counting the replies that go to or come back from the trash with them
+  pub async fn bulk_moderate(
+    &self,
+    filter: &CommentFilter,
+    action: BulkAction,
+  ) -> Result<u64, DbErr> {
+    let txn = self.db.begin().await?;
+    let in_trash = filter.status.as_deref() == Some("deleted");
+    let mut condition = filter.condition();
+    if matches!(action, BulkAction::Restore) && !in_trash {
+      condition = condition.add(wl_comment::Column::Status.eq("spam"));
+    }
+    let ids: Vec<u32> = wl_comment::Entity::find()
+      .select_only()
+      .column(wl_comment::Column::Id)
+      .filter(condition)
+      .into_tuple()
+      .all(&txn)
+      .await?;
+    if ids.is_empty() {
+      return Ok(0);
+    }
+    let status = match action {
+      BulkAction::Approve => "approved",
+      BulkAction::Spam => "spam",
+      BulkAction::Delete if in_trash => {
+        let count = purge_comments(&txn, ids).await?;
+        txn.commit().await?;
+        return Ok(count);
+      }
+      BulkAction::Delete => {
+        let count = trash_comments(&txn, ids).await?;
+        txn.commit().await?;
+        return Ok(count);
+      }
+      BulkAction::Restore if in_trash => {
+        let count = restore_comments(&txn, ids).await?;
+        txn.commit().await?;
+        return Ok(count);
+      }
+      BulkAction::Restore => "approved",
+    };
+    let count = wl_comment::Entity::update_many()
+      .col_expr(wl_comment::Column::Status, Expr::value(status))
+      .col_expr(wl_comment::Column::UpdatedAt, Expr::value(utc_now()))
+      .filter(wl_comment::Column::Id.is_in(ids.clone()))
+      .filter(wl_comment::Column::Status.ne(status))
+      .exec(&txn)
+      .await?
+      .rows_affected;
+    // Approved comments no longer need the attention of a moderator
+    if status == "approved" {
+      wl_report::Entity::delete_many()
+        .filter(wl_report::Column::CommentId.is_in(ids.into_iter().map(|id| id as i32)))
+        .exec(&txn)
+        .await?;
+    }
+    txn.commit().await?;
+    Ok(count)
+  }
+
+  pub async fn get_comment_count_by_status(
+    &self,
+    status: &str,
+    mail: Option<&str>,
+  ) -> Result<u64, DbErr> {
+    let mut select = wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .filter(wl_comment::Column::Status.eq(status));
+    if let Some(mail) = mail {
+      select = select.filter(wl_comment::Column::Mail.eq(mail));
+    }
+    select.count(self.db).await
+  }
+
+  pub async fn get_status_counts(&self) -> Result<Vec<(String, i64)>, DbErr> {
+    wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .select_only()
+      .column(wl_comment::Column::Status)
+      .column_as(wl_comment::Column::Id.count(), "count")
+      .group_by(wl_comment::Column::Status)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  /// Number of comments per `YYYY-MM-DD` day since `since`
+  pub async fn get_daily_counts(&self, since: DateTimeUtc) -> Result<Vec<(String, i64)>, DbErr> {
+    wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .select_only()
+      .column_as(day_of(wl_comment::Column::InsertedAt), "day")
+      .column_as(wl_comment::Column::Id.count(), "count")
+      .filter(wl_comment::Column::InsertedAt.gte(since))
+      .group_by(Expr::col(Alias::new("day")))
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  pub async fn get_top_paths(
+    &self,
+    since: DateTimeUtc,
+    limit: u64,
+  ) -> Result<Vec<(Option<String>, i64)>, DbErr> {
+    wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .select_only()
+      .column(wl_comment::Column::Url)
+      .column_as(wl_comment::Column::Id.count(), "count")
+      .filter(wl_comment::Column::InsertedAt.gte(since))
+      .group_by(wl_comment::Column::Url)
+      .order_by(wl_comment::Column::Id.count(), Order::Desc)
+      .limit(limit)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  pub async fn get_top_commenters(
+    &self,
+    since: DateTimeUtc,
+    limit: u64,
+  ) -> Result<Vec<(Option<String>, Option<String>, i64)>, DbErr> {
+    wl_comment::Entity::find()
+      .filter(wl_comment::Column::DeletedAt.is_null())
+      .select_only()
+      .column(wl_comment::Column::Nick)
+      .column(wl_comment::Column::Mail)
+      .column_as(wl_comment::Column::Id.count(), "count")
+      .filter(wl_comment::Column::InsertedAt.gte(since))
+      .group_by(wl_comment::Column::Nick)
+      .group_by(wl_comment::Column::Mail)
+      .order_by(wl_comment::Column::Id.count(), Order::Desc)
+      .limit(limit)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
 }
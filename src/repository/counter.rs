@@ -1,9 +1,55 @@
 use crate::entities::wl_counter;
 use helpers::time::utc_now;
 use sea_orm::{
-  ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+  IntoActiveModel, Order, QueryFilter, QueryOrder, QuerySelect, Set,
 };
 
+/// Fold `counters` into the one already at `url`, or else the first, adding up their views and
+/// reactions and moving it to `url`
+pub(crate) async fn merge_counters<C: ConnectionTrait>(
+  conn: &C,
+  counters: &[wl_counter::Model],
+  url: String,
+) -> Result<(), DbErr> {
+  let Some(first) = counters.first() else {
+    return Ok(());
+  };
+  let sum = |value: fn(&wl_counter::Model) -> Option<i32>| {
+    counters.iter().filter_map(value).reduce(|a, b| a + b)
+  };
+  let kept = counters
+    .iter()
+    .find(|counter| counter.url == url)
+    .unwrap_or(first);
+  wl_counter::Entity::delete_many()
+    .filter(wl_counter::Column::Id.ne(kept.id))
+    .filter(wl_counter::Column::Id.is_in(counters.iter().map(|counter| counter.id)))
+    .exec(conn)
+    .await?;
+  let mut active_counter = kept.clone().into_active_model();
+  active_counter.url = Set(url);
+  active_counter.time = Set(sum(|counter| counter.time));
+  active_counter.reaction0 = Set(sum(|counter| counter.reaction0));
+  active_counter.reaction1 = Set(sum(|counter| counter.reaction1));
+  active_counter.reaction2 = Set(sum(|counter| counter.reaction2));
+  active_counter.reaction3 = Set(sum(|counter| counter.reaction3));
+  active_counter.reaction4 = Set(sum(|counter| counter.reaction4));
+  active_counter.reaction5 = Set(sum(|counter| counter.reaction5));
+  active_counter.reaction6 = Set(sum(|counter| counter.reaction6));
+  active_counter.reaction7 = Set(sum(|counter| counter.reaction7));
+  active_counter.reaction8 = Set(sum(|counter| counter.reaction8));
+  active_counter.created_at = Set(
+    counters
+      .iter()
+      .filter_map(|counter| counter.created_at)
+      .min(),
+  );
+  active_counter.updated_at = Set(Some(utc_now()));
+  active_counter.update(conn).await?;
+  Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct CounterRepository<'a> {
   pub db: &'a DatabaseConnection,
@@ -34,4 +80,13 @@ impl CounterRepository<'_> {
   ) -> Result<wl_counter::Model, DbErr> {
     active_counter.update(self.db).await
   }
+
+  pub async fn get_top_counters(&self, limit: u64) -> Result<Vec<wl_counter::Model>, DbErr> {
+    wl_counter::Entity::find()
+      .filter(wl_counter::Column::Time.is_not_null())
+      .order_by(wl_counter::Column::Time, Order::Desc)
+      .limit(limit)
+      .all(self.db)
+      .await
+  }
 }
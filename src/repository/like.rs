@@ -0,0 +1,89 @@
+use crate::entities::{wl_comment, wl_like};
+use helpers::time::utc_now;
+use sea_orm::{
+  ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set, TransactionTrait,
+  TryInsertResult,
+  sea_query::{Expr, OnConflict},
+};
+
+/// Logged-in visitors act as themselves, anonymous ones are told apart by a hash of their IP and user agent
+#[derive(Debug, Clone)]
+pub enum VisitorBy<'a> {
+  UserId(i32),
+  Fingerprint(&'a str),
+}
+
+#[derive(Debug, Clone)]
+pub struct LikeRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl LikeRepository<'_> {
+  /// Record or remove a like and keep the counter of the comment in step.
+  /// Returns whether anything changed, so repeated and concurrent calls are harmless: the
+  /// unique indexes of `wl_Like` let only one of them insert the like.
+  pub async fn set_like(
+    &self,
+    comment_id: u32,
+    liker: VisitorBy<'_>,
+    like: bool,
+  ) -> Result<bool, DbErr> {
+    let txn = self.db.begin().await?;
+    let changed = if like {
+      let (user_id, fingerprint, mut conflict) = match liker {
+        VisitorBy::UserId(user_id) => (
+          Some(user_id),
+          None,
+          OnConflict::columns([wl_like::Column::CommentId, wl_like::Column::UserId]),
+        ),
+        VisitorBy::Fingerprint(fingerprint) => (
+          None,
+          Some(fingerprint.to_string()),
+          OnConflict::columns([wl_like::Column::CommentId, wl_like::Column::Fingerprint]),
+        ),
+      };
+      let inserted = wl_like::Entity::insert(wl_like::ActiveModel {
+        comment_id: Set(comment_id as i32),
+        user_id: Set(user_id),
+        fingerprint: Set(fingerprint),
+        created_at: Set(Some(utc_now())),
+        ..Default::default()
+      })
+      // `do_nothing_on` gives MySQL a valid `ON DUPLICATE KEY UPDATE`, and whether the like was
+      // inserted comes from the outcome, since MySQL counts a matched duplicate as affected
+      .on_conflict(conflict.do_nothing_on([wl_like::Column::Id]).to_owned())
+      .do_nothing()
+      .exec(&txn)
+      .await?;
+      matches!(inserted, TryInsertResult::Inserted(_))
+    } else {
+      let mut delete =
+        wl_like::Entity::delete_many().filter(wl_like::Column::CommentId.eq(comment_id as i32));
+      delete = match liker {
+        VisitorBy::UserId(user_id) => delete.filter(wl_like::Column::UserId.eq(user_id)),
+        VisitorBy::Fingerprint(fingerprint) => delete
+          .filter(wl_like::Column::UserId.is_null())
+          .filter(wl_like::Column::Fingerprint.eq(fingerprint)),
+      };
+      delete.exec(&txn).await?.rows_affected > 0
+    };
+    if !changed {
+      return Ok(false);
+    }
+    let like_col = Expr::col(wl_comment::Column::Like).if_null(0);
+    wl_comment::Entity::update_many()
+      .col_expr(
+        wl_comment::Column::Like,
+        if like {
+          like_col.add(1)
+        } else {
+          like_col.sub(1)
+        },
+      )
+      .filter(wl_comment::Column::Id.eq(comment_id))
+      .exec(&txn)
+      .await?;
+    txn.commit().await?;
+    Ok(true)
+  }
+}
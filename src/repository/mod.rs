@@ -1,13 +1,43 @@
+mod article;
 mod comment;
 mod counter;
+mod like;
+mod path_setting;
+mod reaction;
+mod report;
+mod revision;
+mod shadow_ban;
+mod trust;
 pub mod user;
 
-use sea_orm::DatabaseConnection;
+use sea_orm::{
+  DatabaseConnection,
+  sea_query::{Alias, Expr, Func, IntoColumnRef, SimpleExpr},
+};
 
-pub use comment::CommentRepository;
+pub use article::{ArticleRepository, CounterMove};
+pub use comment::{
+  BulkAction, CommentAuthorBy, CommentFilter, CommentRepository, RootCursor, ShadowViewer,
+};
 pub use counter::CounterRepository;
+pub use like::{LikeRepository, VisitorBy};
+pub use path_setting::PathSettingRepository;
+pub use reaction::ReactionRepository;
+pub use report::ReportRepository;
+pub use revision::RevisionRepository;
+pub use shadow_ban::ShadowBanRepository;
+pub use trust::TrustRepository;
 pub use user::UserRepository;
 
+/// The `YYYY-MM-DD` day of a timestamp column, as text on both SQLite and MySQL
+pub(crate) fn day_of(column: impl IntoColumnRef) -> SimpleExpr {
+  Func::cust(Alias::new("SUBSTR"))
+    .arg(Expr::col(column).cast_as(Alias::new("CHAR")))
+    .arg(1)
+    .arg(10)
+    .into()
+}
+
 #[derive(Debug, Clone)]
 pub struct RepositoryManager {
   pub db: DatabaseConnection,
@@ -18,15 +48,47 @@ impl RepositoryManager {
     Self { db }
   }
 
-  pub fn user(&self) -> UserRepository {
+  pub fn user(&self) -> UserRepository<'_> {
     UserRepository { db: &self.db }
   }
 
-  pub fn comment(&self) -> CommentRepository {
+  pub fn article(&self) -> ArticleRepository<'_> {
+    ArticleRepository { db: &self.db }
+  }
+
+  pub fn comment(&self) -> CommentRepository<'_> {
     CommentRepository { db: &self.db }
   }
 
-  pub fn counter(&self) -> CounterRepository {
+  pub fn counter(&self) -> CounterRepository<'_> {
     CounterRepository { db: &self.db }
   }
+
+  pub fn like(&self) -> LikeRepository<'_> {
+    LikeRepository { db: &self.db }
+  }
+
+  pub fn path_setting(&self) -> PathSettingRepository<'_> {
+    PathSettingRepository { db: &self.db }
+  }
+
+  pub fn reaction(&self) -> ReactionRepository<'_> {
+    ReactionRepository { db: &self.db }
+  }
+
+  pub fn report(&self) -> ReportRepository<'_> {
+    ReportRepository { db: &self.db }
+  }
+
+  pub fn revision(&self) -> RevisionRepository<'_> {
+    RevisionRepository { db: &self.db }
+  }
+
+  pub fn shadow_ban(&self) -> ShadowBanRepository<'_> {
+    ShadowBanRepository { db: &self.db }
+  }
+
+  pub fn trust(&self) -> TrustRepository<'_> {
+    TrustRepository { db: &self.db }
+  }
 }
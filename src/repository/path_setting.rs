@@ -0,0 +1,72 @@
+use crate::entities::wl_path_setting;
+use helpers::time::utc_now;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, DeleteResult, EntityTrait,
+  IntoActiveModel, ItemsAndPagesNumber, Order, PaginatorTrait, QueryFilter, QueryOrder, Set,
+};
+
+#[derive(Debug, Clone)]
+pub struct PathSettingRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl PathSettingRepository<'_> {
+  pub async fn get_setting(&self, url: &str) -> Result<Option<wl_path_setting::Model>, DbErr> {
+    wl_path_setting::Entity::find()
+      .filter(wl_path_setting::Column::Url.eq(url))
+      .one(self.db)
+      .await
+  }
+
+  /// Replace the settings of a path, creating them if the path has none yet
+  pub async fn set_setting(
+    &self,
+    url: String,
+    closed: bool,
+    auto_close_days: Option<i32>,
+    comment_audit: Option<bool>,
+  ) -> Result<wl_path_setting::Model, DbErr> {
+    let now = utc_now();
+    match self.get_setting(&url).await? {
+      Some(setting) => {
+        let mut active_setting = setting.into_active_model();
+        active_setting.closed = Set(closed);
+        active_setting.auto_close_days = Set(auto_close_days);
+        active_setting.comment_audit = Set(comment_audit);
+        active_setting.updated_at = Set(Some(now));
+        active_setting.update(self.db).await
+      }
+      None => {
+        wl_path_setting::ActiveModel {
+          url: Set(url),
+          closed: Set(closed),
+          auto_close_days: Set(auto_close_days),
+          comment_audit: Set(comment_audit),
+          created_at: Set(Some(now)),
+          updated_at: Set(Some(now)),
+          ..Default::default()
+        }
+        .insert(self.db)
+        .await
+      }
+    }
+  }
+
+  pub async fn delete_setting(&self, id: u32) -> Result<DeleteResult, DbErr> {
+    wl_path_setting::Entity::delete_by_id(id)
+      .exec(self.db)
+      .await
+  }
+
+  pub async fn get_settings_list(
+    &self,
+    page: u64,
+    page_size: u64,
+  ) -> Result<(ItemsAndPagesNumber, Vec<wl_path_setting::Model>), DbErr> {
+    let paginator = wl_path_setting::Entity::find()
+      .order_by(wl_path_setting::Column::UpdatedAt, Order::Desc)
+      .paginate(self.db, page_size);
+    let settings = paginator.fetch_page(page - 1).await?;
+    Ok((paginator.num_items_and_pages().await?, settings))
+  }
+}
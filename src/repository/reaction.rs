@@ -0,0 +1,110 @@
+use crate::entities::wl_reaction;
+use helpers::time::utc_now;
+use sea_orm::{
+  ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QuerySelect, Set,
+  TryInsertResult, sea_query::OnConflict,
+};
+
+use super::VisitorBy;
+
+fn visitor_condition(visitor: VisitorBy<'_>) -> Condition {
+  match visitor {
+    VisitorBy::UserId(user_id) => Condition::all().add(wl_reaction::Column::UserId.eq(user_id)),
+    VisitorBy::Fingerprint(fingerprint) => Condition::all()
+      .add(wl_reaction::Column::UserId.is_null())
+      .add(wl_reaction::Column::Fingerprint.eq(fingerprint)),
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReactionRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl ReactionRepository<'_> {
+  /// Number of visitors per comment and reaction
+  pub async fn get_reaction_counts(
+    &self,
+    comment_ids: Vec<i32>,
+  ) -> Result<Vec<(i32, String, i64)>, DbErr> {
+    wl_reaction::Entity::find()
+      .select_only()
+      .column(wl_reaction::Column::CommentId)
+      .column(wl_reaction::Column::Reaction)
+      .column_as(wl_reaction::Column::Id.count(), "count")
+      .filter(wl_reaction::Column::CommentId.is_in(comment_ids))
+      .group_by(wl_reaction::Column::CommentId)
+      .group_by(wl_reaction::Column::Reaction)
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  pub async fn get_visitor_reactions(
+    &self,
+    comment_ids: Vec<i32>,
+    visitor: VisitorBy<'_>,
+  ) -> Result<Vec<(i32, String)>, DbErr> {
+    wl_reaction::Entity::find()
+      .select_only()
+      .column(wl_reaction::Column::CommentId)
+      .column(wl_reaction::Column::Reaction)
+      .filter(wl_reaction::Column::CommentId.is_in(comment_ids))
+      .filter(visitor_condition(visitor))
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
+
+  /// Add or take back a reaction, returns whether anything changed. The unique indexes of
+  /// `wl_Reaction` keep concurrent requests from adding the same reaction twice.
+  pub async fn set_reaction(
+    &self,
+    comment_id: u32,
+    reaction: &str,
+    visitor: VisitorBy<'_>,
+    react: bool,
+  ) -> Result<bool, DbErr> {
+    let changed = if react {
+      let (user_id, fingerprint, visitor_col) = match visitor {
+        VisitorBy::UserId(user_id) => (Some(user_id), None, wl_reaction::Column::UserId),
+        VisitorBy::Fingerprint(fingerprint) => (
+          None,
+          Some(fingerprint.to_string()),
+          wl_reaction::Column::Fingerprint,
+        ),
+      };
+      let inserted = wl_reaction::Entity::insert(wl_reaction::ActiveModel {
+        comment_id: Set(comment_id as i32),
+        reaction: Set(reaction.to_string()),
+        user_id: Set(user_id),
+        fingerprint: Set(fingerprint),
+        created_at: Set(Some(utc_now())),
+        ..Default::default()
+      })
+      .on_conflict(
+        OnConflict::columns([
+          wl_reaction::Column::CommentId,
+          wl_reaction::Column::Reaction,
+          visitor_col,
+        ])
+        .do_nothing_on([wl_reaction::Column::Id])
+        .to_owned(),
+      )
+      .do_nothing()
+      .exec(self.db)
+      .await?;
+      matches!(inserted, TryInsertResult::Inserted(_))
+    } else {
+      wl_reaction::Entity::delete_many()
+        .filter(wl_reaction::Column::CommentId.eq(comment_id as i32))
+        .filter(wl_reaction::Column::Reaction.eq(reaction))
+        .filter(visitor_condition(visitor))
+        .exec(self.db)
+        .await?
+        .rows_affected
+        > 0
+    };
+    Ok(changed)
+  }
+}
@@ -0,0 +1,76 @@
+use crate::entities::wl_report;
+use helpers::time::utc_now;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, DeleteResult, EntityTrait, Order,
+  PaginatorTrait, QueryFilter, QueryOrder, Set,
+};
+
+#[derive(Debug, Clone)]
+pub struct ReportRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl ReportRepository<'_> {
+  /// Logged-in reporters are deduplicated by user ID, anonymous ones by IP
+  pub async fn has_reported(
+    &self,
+    comment_id: u32,
+    user_id: Option<i32>,
+    ip: &str,
+  ) -> Result<bool, DbErr> {
+    let mut select =
+      wl_report::Entity::find().filter(wl_report::Column::CommentId.eq(comment_id as i32));
+    if let Some(user_id) = user_id {
+      select = select.filter(wl_report::Column::UserId.eq(user_id));
+    } else {
+      select = select
+        .filter(wl_report::Column::UserId.is_null())
+        .filter(wl_report::Column::Ip.eq(ip));
+    }
+    Ok(select.one(self.db).await?.is_some())
+  }
+
+  pub async fn create_report(
+    &self,
+    comment_id: u32,
+    user_id: Option<i32>,
+    ip: String,
+    reason: Option<String>,
+  ) -> Result<wl_report::Model, DbErr> {
+    wl_report::ActiveModel {
+      comment_id: Set(comment_id as i32),
+      user_id: Set(user_id),
+      ip: Set(Some(ip)),
+      reason: Set(reason),
+      created_at: Set(Some(utc_now())),
+      ..Default::default()
+    }
+    .insert(self.db)
+    .await
+  }
+
+  pub async fn get_report_count(&self, comment_id: u32) -> Result<u64, DbErr> {
+    wl_report::Entity::find()
+      .filter(wl_report::Column::CommentId.eq(comment_id as i32))
+      .count(self.db)
+      .await
+  }
+
+  pub async fn get_reports_by_comment_ids(
+    &self,
+    comment_ids: Vec<i32>,
+  ) -> Result<Vec<wl_report::Model>, DbErr> {
+    wl_report::Entity::find()
+      .filter(wl_report::Column::CommentId.is_in(comment_ids))
+      .order_by(wl_report::Column::CreatedAt, Order::Asc)
+      .all(self.db)
+      .await
+  }
+
+  pub async fn delete_reports(&self, comment_id: u32) -> Result<DeleteResult, DbErr> {
+    wl_report::Entity::delete_many()
+      .filter(wl_report::Column::CommentId.eq(comment_id as i32))
+      .exec(self.db)
+      .await
+  }
+}
@@ -0,0 +1,45 @@
+use crate::entities::wl_comment_revision;
+use helpers::time::utc_now;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Order, QueryFilter,
+  QueryOrder, Set,
+};
+
+#[derive(Debug, Clone)]
+pub struct RevisionRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl RevisionRepository<'_> {
+  /// Keep the body a comment had before it was edited by `user_id`, or by the anonymous
+  /// author through an edit token when there is only a `mail`
+  pub async fn create_revision(
+    &self,
+    comment_id: u32,
+    comment: Option<String>,
+    user_id: Option<i32>,
+    mail: Option<String>,
+  ) -> Result<wl_comment_revision::Model, DbErr> {
+    wl_comment_revision::ActiveModel {
+      comment_id: Set(comment_id as i32),
+      comment: Set(comment),
+      user_id: Set(user_id),
+      mail: Set(mail),
+      created_at: Set(Some(utc_now())),
+      ..Default::default()
+    }
+    .insert(self.db)
+    .await
+  }
+
+  pub async fn get_revisions(
+    &self,
+    comment_id: u32,
+  ) -> Result<Vec<wl_comment_revision::Model>, DbErr> {
+    wl_comment_revision::Entity::find()
+      .filter(wl_comment_revision::Column::CommentId.eq(comment_id as i32))
+      .order_by(wl_comment_revision::Column::Id, Order::Asc)
+      .all(self.db)
+      .await
+  }
+}
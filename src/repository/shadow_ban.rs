@@ -0,0 +1,75 @@
+use crate::entities::wl_shadow_ban;
+use helpers::time::utc_now;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, DeleteResult, EntityTrait,
+  ItemsAndPagesNumber, Order, PaginatorTrait, QueryFilter, QueryOrder, Set,
+};
+
+#[derive(Debug, Clone)]
+pub struct ShadowBanRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl ShadowBanRepository<'_> {
+  /// A commenter is shadow-banned when any of their user ID, email or IP is listed
+  pub async fn is_shadow_banned(
+    &self,
+    user_id: Option<i32>,
+    mail: &str,
+    ip: &str,
+  ) -> Result<bool, DbErr> {
+    let mut condition = Condition::any();
+    if let Some(user_id) = user_id {
+      condition = condition.add(wl_shadow_ban::Column::UserId.eq(user_id));
+    }
+    if !mail.is_empty() {
+      condition = condition.add(wl_shadow_ban::Column::Mail.eq(mail));
+    }
+    if !ip.is_empty() {
+      condition = condition.add(wl_shadow_ban::Column::Ip.eq(ip));
+    }
+    if condition.is_empty() {
+      return Ok(false);
+    }
+    Ok(
+      wl_shadow_ban::Entity::find()
+        .filter(condition)
+        .one(self.db)
+        .await?
+        .is_some(),
+    )
+  }
+
+  pub async fn create_shadow_ban(
+    &self,
+    user_id: Option<i32>,
+    mail: Option<String>,
+    ip: Option<String>,
+  ) -> Result<wl_shadow_ban::Model, DbErr> {
+    wl_shadow_ban::ActiveModel {
+      user_id: Set(user_id),
+      mail: Set(mail),
+      ip: Set(ip),
+      created_at: Set(Some(utc_now())),
+      ..Default::default()
+    }
+    .insert(self.db)
+    .await
+  }
+
+  pub async fn delete_shadow_ban(&self, id: u32) -> Result<DeleteResult, DbErr> {
+    wl_shadow_ban::Entity::delete_by_id(id).exec(self.db).await
+  }
+
+  pub async fn get_shadow_bans_list(
+    &self,
+    page: u64,
+    page_size: u64,
+  ) -> Result<(ItemsAndPagesNumber, Vec<wl_shadow_ban::Model>), DbErr> {
+    let paginator = wl_shadow_ban::Entity::find()
+      .order_by(wl_shadow_ban::Column::CreatedAt, Order::Desc)
+      .paginate(self.db, page_size);
+    let shadow_bans = paginator.fetch_page(page - 1).await?;
+    Ok((paginator.num_items_and_pages().await?, shadow_bans))
+  }
+}
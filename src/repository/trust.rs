@@ -0,0 +1,75 @@
+use crate::{entities::wl_trust, repository::comment::CommentAuthorBy};
+use helpers::time::utc_now;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, DeleteResult, EntityTrait,
+  IntoActiveModel, ItemsAndPagesNumber, Order, PaginatorTrait, QueryFilter, QueryOrder, Set,
+};
+
+#[derive(Debug, Clone)]
+pub struct TrustRepository<'a> {
+  pub db: &'a DatabaseConnection,
+}
+
+impl TrustRepository<'_> {
+  pub async fn get_trust(
+    &self,
+    author: CommentAuthorBy<'_>,
+  ) -> Result<Option<wl_trust::Model>, DbErr> {
+    let mut select = wl_trust::Entity::find();
+    match author {
+      CommentAuthorBy::UserId(user_id) => {
+        select = select.filter(wl_trust::Column::UserId.eq(user_id))
+      }
+      CommentAuthorBy::Mail(mail) => select = select.filter(wl_trust::Column::Mail.eq(mail)),
+    }
+    select.one(self.db).await
+  }
+
+  pub async fn set_trust(
+    &self,
+    author: CommentAuthorBy<'_>,
+    trusted: bool,
+  ) -> Result<wl_trust::Model, DbErr> {
+    let now = utc_now();
+    match self.get_trust(author.clone()).await? {
+      Some(trust) => {
+        let mut active_trust = trust.into_active_model();
+        active_trust.trusted = Set(trusted);
+        active_trust.updated_at = Set(Some(now));
+        active_trust.update(self.db).await
+      }
+      None => {
+        let (user_id, mail) = match author {
+          CommentAuthorBy::UserId(user_id) => (Some(user_id), None),
+          CommentAuthorBy::Mail(mail) => (None, Some(mail.to_string())),
+        };
+        wl_trust::ActiveModel {
+          user_id: Set(user_id),
+          mail: Set(mail),
+          trusted: Set(trusted),
+          created_at: Set(Some(now)),
+          updated_at: Set(Some(now)),
+          ..Default::default()
+        }
+        .insert(self.db)
+        .await
+      }
+    }
+  }
+
+  pub async fn delete_trust(&self, id: u32) -> Result<DeleteResult, DbErr> {
+    wl_trust::Entity::delete_by_id(id).exec(self.db).await
+  }
+
+  pub async fn get_trusts_list(
+    &self,
+    page: u64,
+    page_size: u64,
+  ) -> Result<(ItemsAndPagesNumber, Vec<wl_trust::Model>), DbErr> {
+    let paginator = wl_trust::Entity::find()
+      .order_by(wl_trust::Column::UpdatedAt, Order::Desc)
+      .paginate(self.db, page_size);
+    let trusts = paginator.fetch_page(page - 1).await?;
+    Ok((paginator.num_items_and_pages().await?, trusts))
+  }
+}
@@ -1,6 +1,9 @@
-use crate::entities::wl_users;
+use crate::{entities::wl_users, repository::day_of};
 use sea_orm::{
   ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+  QuerySelect,
+  prelude::DateTimeUtc,
+  sea_query::{Alias, Expr},
 };
 
 pub enum UserQueryBy<'a> {
@@ -117,4 +120,17 @@ impl<'a> UserRepository<'a> {
       Ok(false)
     }
   }
+
+  /// Number of registrations per `YYYY-MM-DD` day since `since`
+  pub async fn get_daily_counts(&self, since: DateTimeUtc) -> Result<Vec<(String, i64)>, DbErr> {
+    wl_users::Entity::find()
+      .select_only()
+      .column_as(day_of(wl_users::Column::CreatedAt), "day")
+      .column_as(wl_users::Column::Id.count(), "count")
+      .filter(wl_users::Column::CreatedAt.gte(since))
+      .group_by(Expr::col(Alias::new("day")))
+      .into_tuple()
+      .all(self.db)
+      .await
+  }
 }